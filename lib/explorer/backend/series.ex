@@ -7,7 +7,17 @@ defmodule Explorer.Backend.Series do
 
   @type s :: Explorer.Series.t()
   @type df :: Explorer.DataFrame.t()
-  @type dtype :: :integer | :float | :boolean | :string | :date | :datetime
+  @type dtype ::
+          :integer
+          | :float
+          | :boolean
+          | :string
+          | :date
+          | :datetime
+          | :time
+          | :duration
+          | :category
+          | :list
 
   # Conversion
 
@@ -34,8 +44,8 @@ defmodule Explorer.Backend.Series do
   # Aggregation
 
   @callback sum(s) :: number()
-  @callback min(s) :: number() | Date.t() | NaiveDateTime.t()
-  @callback max(s) :: number() | Date.t() | NaiveDateTime.t()
+  @callback min(s) :: number() | Date.t() | NaiveDateTime.t() | Time.t()
+  @callback max(s) :: number() | Date.t() | NaiveDateTime.t() | Time.t()
   @callback mean(s) :: float()
   @callback median(s) :: float()
   @callback var(s) :: float()
@@ -82,8 +92,22 @@ defmodule Explorer.Backend.Series do
 
   @callback distinct(s) :: s
   @callback n_distinct(s) :: integer()
+  @callback categories(s) :: s
   @callback count(s) :: df
 
+  # List
+
+  @callback list_lengths(s) :: s
+  @callback list_get(s, idx :: integer()) :: s
+  @callback list_contains(s, value :: number() | String.t()) :: s
+  @callback list_join(s, separator :: String.t()) :: s
+  @callback list_sum(s) :: s
+  @callback list_mean(s) :: s
+  @callback list_min(s) :: s
+  @callback list_max(s) :: s
+  @callback list_sort(s, reverse? :: boolean()) :: s
+  @callback list_unique(s) :: s
+
   # Rolling
 
   @callback rolling_sum(s, window_size :: integer(), weight :: float(), ignore_nil? :: boolean()) ::
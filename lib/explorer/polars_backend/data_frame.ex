@@ -30,42 +30,40 @@ defmodule Explorer.PolarsBackend.DataFrame do
       ) do
     max_rows = if max_rows == Inf, do: nil, else: max_rows
 
-    df =
-      Native.df_read_csv(
-        filename,
-        1000,
-        header?,
-        max_rows,
-        skip_rows,
-        nil,
-        delimiter,
-        true,
-        with_columns,
-        dtypes,
-        null_character,
-        encoding
+    options =
+      Shared.csv_read_options(
+        has_header: header?,
+        stop_after_n_rows: max_rows,
+        skip_rows: skip_rows,
+        delimiter: delimiter,
+        columns: with_columns,
+        dtypes: dtypes,
+        encoding: encoding,
+        null_char: null_character
       )
 
-    case df do
+    case Native.df_read_csv(filename, options) do
       {:ok, df} -> {:ok, Shared.to_dataframe(df)}
-      {:error, error} -> {:error, error}
+      {:error, error} -> {:error, Shared.format_error(error)}
     end
   end
 
   @impl true
   def write_csv(%DataFrame{data: df}, filename, header?, delimiter) do
     <<delimiter::utf8>> = delimiter
+    options = Shared.csv_write_options(has_headers: header?, delimiter: delimiter)
 
-    case Native.df_to_csv_file(df, filename, header?, delimiter) do
+    case Native.df_to_csv_file(df, filename, options, nil) do
       {:ok, _} -> {:ok, filename}
-      {:error, error} -> {:error, error}
+      {:error, error} -> {:error, Shared.format_error(error)}
     end
   end
 
   @impl true
   def to_binary(%DataFrame{} = df, header?, delimiter) do
     <<delimiter::utf8>> = delimiter
-    Shared.apply_native(df, :df_to_csv, [header?, delimiter])
+    options = Shared.csv_write_options(has_headers: header?, delimiter: delimiter)
+    Shared.apply_native(df, :df_to_csv, [options])
   end
 
   # Conversion
@@ -397,11 +395,12 @@ end
 
 defimpl Inspect, for: Explorer.PolarsBackend.DataFrame do
   alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
 
   def inspect(df, _opts) do
     case Native.df_as_str(df) do
       {:ok, str} -> str
-      {:error, error} -> raise "#{error}"
+      {:error, error} -> raise Shared.format_error(error)
     end
   end
 end
@@ -7,46 +7,21 @@ defmodule Explorer.PolarsBackend.Native do
 
   defstruct [:inner]
 
-  def df_read_csv(
-        _filename,
-        _infer_schema_length,
-        _has_header,
-        _stop_after_n_rows,
-        _skip_rows,
-        _projection,
-        _sep,
-        _rechunk,
-        _with_columns,
-        _dtypes,
-        _null_char,
-        _encoding
-      ),
-      do: err()
-
-  def df_to_csv(
-        _df,
-        _has_headers,
-        _delimiter
-      ),
-      do: err()
-
-  def df_to_csv_file(
-        _df,
-        _filename,
-        _has_headers,
-        _delimiter
-      ),
-      do: err()
-
   def df_as_str(_df), do: err()
+  def df_cancel_async(_operation), do: err()
   def df_cast(_df, _column, _dtype), do: err()
   def df_clone(_df), do: err()
+  def df_close_csv_writer(_writer), do: err()
   def df_column(_df, _name), do: err()
   def df_columns(_def), do: err()
+  def df_csv_schema(_filename, _infer_schema_length, _has_header, _sep), do: err()
   def df_drop(_df, _name), do: err()
   def df_drop_duplicates(_df, _maintain_order, _subset), do: err()
   def df_drop_nulls(_df, _subset), do: err()
   def df_dtypes(_df), do: err()
+  def df_dump_ipc(_df), do: err()
+  def df_dump_ipc_stream(_df), do: err()
+  def df_dump_parquet(_df, _compression, _row_group_size, _statistics), do: err()
   def df_explode(_df, _cols), do: err()
   def df_fill_none(_df, _strategy), do: err()
   def df_filter(_df, _mask), do: err()
@@ -55,12 +30,20 @@ defmodule Explorer.PolarsBackend.Native do
   def df_get_columns(_df), do: err()
   def df_groups(_df, _colnames), do: err()
   def df_groupby_agg(_df, _groups, _aggs), do: err()
+  def df_groupby_agg_async(_ref, _df, _groups, _aggs), do: err()
   def df_head(_df, _length), do: err()
   def df_height(_df), do: err()
   def df_hstack(_df, _cols), do: err()
   def df_is_duplicated(_df), do: err()
   def df_is_unique(_df), do: err()
   def df_join(_df, _other, _left_on, _right_on, _how), do: err()
+  def df_join_async(_ref, _df, _other, _left_on, _right_on, _how), do: err()
+  def df_load_csv(_binary, _options), do: err()
+  def df_load_ipc(_binary, _columns), do: err()
+  def df_load_ipc_stream(_binary, _columns), do: err()
+  def df_load_json(_binary, _infer_schema_length, _dtypes, _columns), do: err()
+  def df_load_ndjson(_binary, _infer_schema_length, _dtypes, _columns), do: err()
+  def df_load_parquet(_binary, _columns, _n_rows, _predicates), do: err()
   def df_max(_df), do: err()
   def df_mean(_df), do: err()
   def df_median(_df), do: err()
@@ -68,10 +51,22 @@ defmodule Explorer.PolarsBackend.Native do
   def df_min(_df), do: err()
   def df_n_chunks(_df), do: err()
   def df_new(_cols), do: err()
+  def df_next_csv_batch(_reader, _n_rows), do: err()
+  def df_open_csv_writer(_filename, _options, _append), do: err()
+  def df_parquet_metadata(_filename), do: err()
   def df_pivot_wider(_df, _id_cols, _pivot_column, _values_columns), do: err()
   def df_quantile(_df, _quant), do: err()
-  def df_read_json(_filename, _type), do: err()
-  def df_read_parquet(_filename), do: err()
+  def df_read_csv(_filename, _options), do: err()
+  def df_read_csv_async(_ref, _filename, _options), do: err()
+  def df_read_csv_batched(_filename, _options), do: err()
+  def df_read_dataset(_paths, _format, _csv_options, _source_column), do: err()
+  def df_read_ipc(_filename, _columns), do: err()
+  def df_read_ipc_stream(_filename, _columns), do: err()
+  def df_read_json(_filename, _infer_schema_length, _dtypes, _columns), do: err()
+  def df_read_ndjson(_filename, _infer_schema_length, _dtypes, _columns), do: err()
+  def df_read_parquet(_filename, _columns, _n_rows, _predicates), do: err()
+  def df_read_parquet_async(_ref, _filename, _columns, _n_rows, _predicates), do: err()
+  def df_read_partitioned(_root, _filters), do: err()
   def df_replace(_df, _col, _new_col), do: err()
   def df_select(_df, _selection), do: err()
   def df_select_at_idx(_df, _idx), do: err()
@@ -80,16 +75,33 @@ defmodule Explorer.PolarsBackend.Native do
   def df_shift(_df, _periods), do: err()
   def df_slice(_df, _offset, _length), do: err()
   def df_sort(_df, _by, _reverse), do: err()
+  def df_sort_async(_ref, _df, _by, _reverse), do: err()
   def df_stdev(_df), do: err()
   def df_sum(_df), do: err()
   def df_tail(_df, _length), do: err()
   def df_take(_df, _indices), do: err()
   def df_take_with_series(_df, _indices), do: err()
+  def df_to_csv(_df, _options), do: err()
+  def df_to_csv_file(_df, _filename, _options, _compression), do: err()
+  def df_to_csv_file_async(_ref, _df, _filename, _options, _compression), do: err()
   def df_to_dummies(_df), do: err()
+  def df_to_json(_df, _orient), do: err()
+  def df_to_json_file(_df, _filename, _orient), do: err()
+  def df_to_ndjson(_df), do: err()
+  def df_to_ndjson_file(_df, _filename), do: err()
   def df_var(_df), do: err()
   def df_vstack(_df, _other), do: err()
   def df_width(_df), do: err()
   def df_with_column(_df, _col), do: err()
+  def df_write_csv_batch(_writer, _df), do: err()
+  def df_write_ipc(_df, _filename), do: err()
+  def df_write_ipc_stream(_df, _filename), do: err()
+  def df_write_parquet(_df, _filename, _compression, _row_group_size, _statistics), do: err()
+
+  def df_write_parquet_async(_ref, _df, _filename, _compression, _row_group_size, _statistics),
+    do: err()
+
+  def df_write_partitioned(_df, _root, _partition_by, _compression), do: err()
 
   # Series
   def s_add(_s, _other), do: err()
@@ -98,6 +110,7 @@ defmodule Explorer.PolarsBackend.Native do
   def s_argsort(_s, _reverse), do: err()
   def s_as_str(_s), do: err()
   def s_cast(_s, _dtype), do: err()
+  def s_categories(_s), do: err()
   def s_clone(_s), do: err()
   def s_cum_max(_s, _reverse), do: err()
   def s_cum_min(_s, _reverse), do: err()
@@ -119,6 +132,16 @@ defmodule Explorer.PolarsBackend.Native do
   def s_is_unique(_s), do: err()
   def s_len(_s), do: err()
   def s_limit(_s, _num_elem), do: err()
+  def s_list_contains(_s, _value), do: err()
+  def s_list_get(_s, _idx), do: err()
+  def s_list_join(_s, _separator), do: err()
+  def s_list_lengths(_s), do: err()
+  def s_list_max(_s), do: err()
+  def s_list_mean(_s), do: err()
+  def s_list_min(_s), do: err()
+  def s_list_sort(_s, _reverse), do: err()
+  def s_list_sum(_s), do: err()
+  def s_list_unique(_s), do: err()
   def s_lt(_s, _rhs), do: err()
   def s_lt_eq(_s, _rhs), do: err()
   def s_max(_s), do: err()
@@ -132,9 +155,20 @@ defmodule Explorer.PolarsBackend.Native do
   def s_new_bool(_name, _val), do: err()
   def s_new_date32(_name, _val), do: err()
   def s_new_date64(_name, _val), do: err()
+  def s_new_duration_ms(_name, _val), do: err()
+  def s_new_duration_ns(_name, _val), do: err()
+  def s_new_f32(_name, _val), do: err()
   def s_new_f64(_name, _val), do: err()
+  def s_new_i8(_name, _val), do: err()
+  def s_new_i16(_name, _val), do: err()
+  def s_new_i32(_name, _val), do: err()
   def s_new_i64(_name, _val), do: err()
+  def s_new_list(_name, _val), do: err()
   def s_new_str(_name, _val), do: err()
+  def s_new_time64(_name, _val), do: err()
+  def s_new_u8(_name, _val), do: err()
+  def s_new_u16(_name, _val), do: err()
+  def s_new_u32(_name, _val), do: err()
   def s_new_u64(_name, _val), do: err()
   def s_not(_s), do: err()
   def s_null_count(_s), do: err()
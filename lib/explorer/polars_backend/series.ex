@@ -40,9 +40,29 @@ defmodule Explorer.PolarsBackend.Series do
 
         :datetime ->
           data |> Enum.map(&encode_datetime/1) |> then(&Native.s_new_date64(name, &1))
+
+        :time ->
+          data |> Enum.map(&encode_time/1) |> then(&Native.s_new_time64(name, &1))
+
+        :duration ->
+          Native.s_new_duration_ms(name, data)
+
+        :list ->
+          # Lists that are all empty or nil have no values to infer from.
+          inner_type =
+            data |> Stream.reject(&is_nil/1) |> Stream.concat() |> Explorer.Shared.check_types!()
+
+          inner_type = inner_type || :integer
+
+          data
+          |> Enum.map(&(&1 && from_list(&1, inner_type).data))
+          |> then(&Native.s_new_list(name, &1))
       end
 
-    %Series{data: series, dtype: type}
+    case series do
+      {:ok, series} -> %Series{data: series, dtype: type}
+      {:error, error} -> raise Shared.format_error(error)
+    end
   end
 
   @impl true
@@ -50,12 +70,14 @@ defmodule Explorer.PolarsBackend.Series do
     list =
       case Native.s_to_list(series) do
         {:ok, list} -> list
-        {:error, e} -> raise "#{e}"
+        {:error, error} -> raise Shared.format_error(error)
       end
 
     case dtype do
       :date -> Enum.map(list, &decode_date/1)
       :datetime -> Enum.map(list, &decode_datetime/1)
+      :time -> Enum.map(list, &decode_time/1)
+      :list -> Enum.map(list, list_decoder(series))
       _ -> list
     end
   end
@@ -117,6 +139,8 @@ defmodule Explorer.PolarsBackend.Series do
     case dtype do
       :date -> decode_date(value)
       :datetime -> decode_datetime(value)
+      :time -> decode_time(value)
+      :list -> list_decoder(series).(value)
       _ -> value
     end
   end
@@ -139,6 +163,12 @@ defmodule Explorer.PolarsBackend.Series do
       |> Shared.apply_native(:s_min)
       |> decode_datetime()
 
+  def min(%Series{dtype: :time} = series),
+    do:
+      series
+      |> Shared.apply_native(:s_min)
+      |> decode_time()
+
   def min(series), do: Shared.apply_native(series, :s_min)
 
   @impl true
@@ -154,6 +184,12 @@ defmodule Explorer.PolarsBackend.Series do
       |> Shared.apply_native(:s_max)
       |> decode_datetime()
 
+  def max(%Series{dtype: :time} = series),
+    do:
+      series
+      |> Shared.apply_native(:s_max)
+      |> decode_time()
+
   def max(series), do: Shared.apply_native(series, :s_max)
 
   @impl true
@@ -280,6 +316,9 @@ defmodule Explorer.PolarsBackend.Series do
   @impl true
   def n_distinct(series), do: Shared.apply_native(series, :s_n_unique)
 
+  @impl true
+  def categories(series), do: Shared.apply_native(series, :s_categories)
+
   @impl true
   def count(series),
     do:
@@ -291,6 +330,38 @@ defmodule Explorer.PolarsBackend.Series do
       |> DataFrame.rename(["values", "counts"])
       |> DataFrame.mutate(counts: &Series.cast(&1["counts"], :integer))
 
+  # List
+
+  @impl true
+  def list_lengths(series), do: Shared.apply_native(series, :s_list_lengths)
+
+  @impl true
+  def list_get(series, idx), do: Shared.apply_native(series, :s_list_get, [idx])
+
+  @impl true
+  def list_contains(series, value), do: Shared.apply_native(series, :s_list_contains, [value])
+
+  @impl true
+  def list_join(series, separator), do: Shared.apply_native(series, :s_list_join, [separator])
+
+  @impl true
+  def list_sum(series), do: Shared.apply_native(series, :s_list_sum)
+
+  @impl true
+  def list_mean(series), do: Shared.apply_native(series, :s_list_mean)
+
+  @impl true
+  def list_min(series), do: Shared.apply_native(series, :s_list_min)
+
+  @impl true
+  def list_max(series), do: Shared.apply_native(series, :s_list_max)
+
+  @impl true
+  def list_sort(series, reverse?), do: Shared.apply_native(series, :s_list_sort, [reverse?])
+
+  @impl true
+  def list_unique(series), do: Shared.apply_native(series, :s_list_unique)
+
   # Rolling
 
   @impl true
@@ -346,15 +417,41 @@ defmodule Explorer.PolarsBackend.Series do
     do: NaiveDateTime.add(~N[1970-01-01 00:00:00], date, :millisecond)
 
   defp decode_datetime(date) when is_nil(date), do: nil
+
+  defp encode_time(%Time{} = time), do: Time.diff(time, ~T[00:00:00], :nanosecond)
+  defp encode_time(time) when is_nil(time), do: nil
+
+  # Elixir times stop at microseconds, so nanoseconds are truncated.
+  defp decode_time(time) when is_integer(time),
+    do: Time.add(~T[00:00:00.000000], div(time, 1000), :microsecond)
+
+  defp decode_time(time) when is_nil(time), do: nil
+
+  # Returns a function that decodes a single row of a list series according to
+  # the dtype of its inner values.
+  defp list_decoder(series) do
+    "list [" <> inner = Shared.apply_native(series, :s_dtype)
+
+    decode =
+      case inner |> String.slice(0..-2) |> Shared.normalise_dtype() do
+        :date -> &decode_date/1
+        :datetime -> &decode_datetime/1
+        :time -> &decode_time/1
+        _ -> & &1
+      end
+
+    &(&1 && Enum.map(&1, decode))
+  end
 end
 
 defimpl Inspect, for: Explorer.PolarsBackend.Series do
   alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
 
   def inspect(s, _opts) do
     case Native.s_as_str(s) do
       {:ok, str} -> str
-      {:error, error} -> raise "#{error}"
+      {:error, error} -> raise Shared.format_error(error)
     end
   end
 end
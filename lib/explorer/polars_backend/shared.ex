@@ -20,6 +20,85 @@ defmodule Explorer.PolarsBackend.Shared do
     unwrap(result, groups)
   end
 
+  # Calls the `_async` variant of a native function, which runs on a Rust
+  # thread pool, and waits for the `{ref, result}` message it sends back.
+  # `{:progress, ref, rows_processed}` messages are passed to `:on_progress`,
+  # and the operation is cancelled if it is not done within `:timeout` ms.
+  def apply_native_async(fun, args, opts \\ []) do
+    on_progress = Keyword.get(opts, :on_progress, fn _rows -> :ok end)
+    deadline = deadline(Keyword.get(opts, :timeout, :infinity))
+    ref = make_ref()
+
+    with {:ok, operation} <- apply(Native, fun, [ref | args]) do
+      await_native(ref, operation, on_progress, deadline)
+    end
+  end
+
+  defp deadline(:infinity), do: :infinity
+  defp deadline(timeout), do: System.monotonic_time(:millisecond) + timeout
+
+  defp remaining(:infinity), do: :infinity
+  defp remaining(deadline), do: max(deadline - System.monotonic_time(:millisecond), 0)
+
+  defp await_native(ref, operation, on_progress, deadline) do
+    receive do
+      {:progress, ^ref, rows} ->
+        on_progress.(rows)
+        await_native(ref, operation, on_progress, deadline)
+
+      {^ref, result} ->
+        result
+    after
+      remaining(deadline) ->
+        Native.df_cancel_async(operation)
+        flush_native(ref)
+    end
+  end
+
+  # Waits for the cancelled operation to finish so that no stray messages
+  # are left behind in the mailbox.
+  defp flush_native(ref) do
+    receive do
+      {:progress, ^ref, _rows} -> flush_native(ref)
+      {^ref, {:error, {:cancelled, _}}} -> {:error, {:timeout, "operation timed out"}}
+      {^ref, result} -> result
+    end
+  end
+
+  # The CSV reader and writer NIFs decode their options from maps that must
+  # hold every key, so the defaults are filled in here.
+  @csv_read_options %{
+    infer_schema_length: 1000,
+    has_header: true,
+    stop_after_n_rows: nil,
+    skip_rows: 0,
+    projection: nil,
+    delimiter: ",",
+    rechunk: true,
+    columns: nil,
+    dtypes: nil,
+    encoding: "utf8",
+    null_char: "NA",
+    quote_char: "\"",
+    comment_char: nil,
+    column_null_values: nil,
+    skip_footer: 0
+  }
+
+  def csv_read_options(opts \\ []), do: Enum.into(opts, @csv_read_options)
+
+  @csv_write_options %{
+    has_headers: true,
+    delimiter: ?,,
+    quote_style: "necessary",
+    null_value: "",
+    date_format: nil,
+    datetime_format: nil,
+    float_precision: nil
+  }
+
+  def csv_write_options(opts \\ []), do: Enum.into(opts, @csv_write_options)
+
   def to_polars_df(%DataFrame{data: %PolarsDataFrame{} = polars_df}), do: polars_df
   def to_polars_df(%PolarsDataFrame{} = polars_df), do: polars_df
 
@@ -44,15 +123,28 @@ defmodule Explorer.PolarsBackend.Shared do
   def unwrap({:ok, %PolarsSeries{} = series}, _), do: to_series(series)
   def unwrap({:ok, %PolarsDataFrame{} = df}, groups), do: to_dataframe(df, groups)
   def unwrap({:ok, value}, _), do: value
-  def unwrap({:error, error}, _), do: raise("#{error}")
+  def unwrap({:error, error}, _), do: raise(format_error(error))
+
+  def format_error({kind, details}) when is_binary(details), do: "#{kind}: #{details}"
+  def format_error({kind, details}), do: "#{kind}: #{inspect(details)}"
 
+  def normalise_dtype("u8"), do: :integer
+  def normalise_dtype("u16"), do: :integer
   def normalise_dtype("u32"), do: :integer
+  def normalise_dtype("u64"), do: :integer
+  def normalise_dtype("i8"), do: :integer
+  def normalise_dtype("i16"), do: :integer
   def normalise_dtype("i32"), do: :integer
   def normalise_dtype("i64"), do: :integer
+  def normalise_dtype("f32"), do: :float
   def normalise_dtype("f64"), do: :float
   def normalise_dtype("bool"), do: :boolean
   def normalise_dtype("str"), do: :string
+  def normalise_dtype("cat"), do: :category
   def normalise_dtype("date32(days)"), do: :date
   def normalise_dtype("date64(ms)"), do: :datetime
-  def normalise_dtype("list [u32]"), do: :list
+  def normalise_dtype("time64(ns)"), do: :time
+  def normalise_dtype("duration(ms)"), do: :duration
+  def normalise_dtype("duration(ns)"), do: :duration
+  def normalise_dtype("list [" <> _), do: :list
 end
@@ -10,6 +10,13 @@ defmodule Explorer.Series do
     * `:string` - UTF-8 encoded binary
     * `:date` - Date type that unwraps to `Elixir.Date`
     * `:datetime` - DateTime type that unwraps to `Elixir.NaiveDateTime`
+    * `:time` - Time of day type that unwraps to `Elixir.Time`
+    * `:duration` - Duration type, such as the difference of two dates, that unwraps to an
+      integer in the duration's time unit
+    * `:category` - Categorical type for low-cardinality strings, stored as integer codes shared
+      by all series, that unwraps to UTF-8 encoded binaries. Made by casting a `:string` series
+    * `:list` - List type where each element is a list of values of a single inner dtype, or nil.
+      Made from a list of lists
 
   A series must consist of a single data type only. Series are nullable, but may not consist only of
   nils.
@@ -26,7 +33,17 @@ defmodule Explorer.Series do
   import Kernel, except: [length: 1]
 
   @type data :: Explorer.Backend.Series.t()
-  @type dtype :: :float | :integer | :boolean | :string | :date | :datetime
+  @type dtype ::
+          :float
+          | :integer
+          | :boolean
+          | :string
+          | :date
+          | :datetime
+          | :time
+          | :duration
+          | :category
+          | :list
   @type t :: %Series{data: data, dtype: dtype}
 
   @enforce_keys [:data, :dtype]
@@ -80,6 +97,8 @@ defmodule Explorer.Series do
   ## Options
 
     * `:backend` - The backend to allocate the series on. 
+    * `:dtype` - The dtype of the series. Only `:duration` may be given for a list of
+      integers, which are taken as milliseconds; otherwise it must match the inferred dtype.
 
   ## Examples
 
@@ -107,7 +126,7 @@ defmodule Explorer.Series do
   @spec from_list(list :: list(), opts :: Keyword.t()) :: Series.t()
   def from_list(list, opts \\ []) do
     backend = backend_from_options!(opts)
-    type = check_types(list)
+    type = list |> check_types() |> dtype_from_options!(opts)
     backend.from_list(list, type)
   end
 
@@ -207,6 +226,9 @@ defmodule Explorer.Series do
     * `:string` - UTF-8 encoded binary
     * `:date` - Date type that unwraps to `Elixir.Date`
     * `:datetime` - DateTime type that unwraps to `Elixir.NaiveDateTime`
+    * `:time` - Time of day type that unwraps to `Elixir.Time`
+    * `:duration` - Duration type that unwraps to an integer in the duration's time unit
+    * `:category` - Categorical type that unwraps to UTF-8 encoded binaries
 
   ## Examples
 
@@ -453,6 +475,8 @@ defmodule Explorer.Series do
     * `:float`
     * `:date`
     * `:datetime`
+    * `:time`
+    * `:duration`
 
   ## Examples
 
@@ -474,14 +498,15 @@ defmodule Explorer.Series do
 
       iex> s = Explorer.Series.from_list(["a", "b", "c"])
       iex> Explorer.Series.min(s)
-      ** (ArgumentError) Explorer.Series.min/1 not implemented for dtype :string. Valid dtypes are [:integer, :float, :date, :datetime].
+      ** (ArgumentError) Explorer.Series.min/1 not implemented for dtype :string. Valid dtypes are [:integer, :float, :date, :datetime, :time, :duration].
   """
-  @spec min(series :: Series.t()) :: number() | Date.t() | NaiveDateTime.t()
-  def min(%Series{dtype: dtype} = series) when dtype in [:integer, :float, :date, :datetime],
-    do: apply_impl(series, :min)
+  @spec min(series :: Series.t()) :: number() | Date.t() | NaiveDateTime.t() | Time.t()
+  def min(%Series{dtype: dtype} = series)
+      when dtype in [:integer, :float, :date, :datetime, :time, :duration],
+      do: apply_impl(series, :min)
 
   def min(%Series{dtype: dtype}),
-    do: dtype_error("min/1", dtype, [:integer, :float, :date, :datetime])
+    do: dtype_error("min/1", dtype, [:integer, :float, :date, :datetime, :time, :duration])
 
   @doc """
   Gets the maximum value of the series.
@@ -492,6 +517,8 @@ defmodule Explorer.Series do
     * `:float`
     * `:date`
     * `:datetime`
+    * `:time`
+    * `:duration`
 
   ## Examples
 
@@ -513,14 +540,15 @@ defmodule Explorer.Series do
 
       iex> s = Explorer.Series.from_list(["a", "b", "c"])
       iex> Explorer.Series.max(s)
-      ** (ArgumentError) Explorer.Series.max/1 not implemented for dtype :string. Valid dtypes are [:integer, :float, :date, :datetime].
+      ** (ArgumentError) Explorer.Series.max/1 not implemented for dtype :string. Valid dtypes are [:integer, :float, :date, :datetime, :time, :duration].
   """
-  @spec max(series :: Series.t()) :: number() | Date.t() | NaiveDateTime.t()
-  def max(%Series{dtype: dtype} = series) when dtype in [:integer, :float, :date, :datetime],
-    do: apply_impl(series, :max)
+  @spec max(series :: Series.t()) :: number() | Date.t() | NaiveDateTime.t() | Time.t()
+  def max(%Series{dtype: dtype} = series)
+      when dtype in [:integer, :float, :date, :datetime, :time, :duration],
+      do: apply_impl(series, :max)
 
   def max(%Series{dtype: dtype}),
-    do: dtype_error("max/1", dtype, [:integer, :float, :date, :datetime])
+    do: dtype_error("max/1", dtype, [:integer, :float, :date, :datetime, :time, :duration])
 
   @doc """
   Gets the mean value of the series.
@@ -784,11 +812,13 @@ defmodule Explorer.Series do
   Adds right to left, element-wise.
 
   When mixing floats and integers, the resulting series will have dtype `:float`.
+  Adding a `:duration` series to a `:date` or `:datetime` series results in a `:datetime` series.
 
   ## Supported dtypes
 
     * `:integer`
     * `:float`
+    * `:date`, `:datetime` and `:duration`, as described above
   """
   @spec add(left :: Series.t(), right :: Series.t() | number()) :: Series.t()
   def add(%Series{dtype: left_dtype} = left, %Series{dtype: right_dtype} = right)
@@ -796,6 +826,11 @@ defmodule Explorer.Series do
              right_dtype in [:integer, :float],
       do: apply_impl(left, :add, [right])
 
+  def add(%Series{dtype: left_dtype} = left, %Series{dtype: right_dtype} = right)
+      when (left_dtype in [:date, :datetime] and right_dtype == :duration) or
+             (left_dtype == :duration and right_dtype in [:date, :datetime]),
+      do: apply_impl(left, :add, [right])
+
   def add(%Series{dtype: left_dtype}, %Series{dtype: right_dtype}),
     do: dtype_mismatch_error("add/2", left_dtype, right_dtype)
 
@@ -809,11 +844,14 @@ defmodule Explorer.Series do
   Subtracts right from left, element-wise.
 
   When mixing floats and integers, the resulting series will have dtype `:float`.
+  Subtracting two `:date` or `:datetime` series results in a `:duration` series, and
+  subtracting a `:duration` series from either results in a `:datetime` series.
 
   ## Supported dtypes
 
     * `:integer`
     * `:float`
+    * `:date`, `:datetime` and `:duration`, as described above
   """
   @spec subtract(left :: Series.t(), right :: Series.t() | number()) :: Series.t()
   def subtract(%Series{dtype: left_dtype} = left, %Series{dtype: right_dtype} = right)
@@ -821,6 +859,10 @@ defmodule Explorer.Series do
              right_dtype in [:integer, :float],
       do: apply_impl(left, :subtract, [right])
 
+  def subtract(%Series{dtype: left_dtype} = left, %Series{dtype: right_dtype} = right)
+      when left_dtype in [:date, :datetime] and right_dtype in [:date, :datetime, :duration],
+      do: apply_impl(left, :subtract, [right])
+
   def subtract(%Series{dtype: left_dtype}, %Series{dtype: right_dtype}),
     do: dtype_mismatch_error("subtract/2", left_dtype, right_dtype)
 
@@ -1208,6 +1250,23 @@ defmodule Explorer.Series do
   """
   def n_distinct(series), do: apply_impl(series, :n_distinct)
 
+  @doc """
+  Returns the categories of a categorical series, ordered by their codes.
+
+  ## Supported dtypes
+
+    * `:category`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list(["b", "a", "b", nil]) |> Explorer.Series.cast(:category)
+      iex> Explorer.Series.categories(s) |> Explorer.Series.to_list() |> Enum.sort()
+      ["a", "b"]
+  """
+  @spec categories(series :: Series.t()) :: Series.t()
+  def categories(%Series{dtype: :category} = series), do: apply_impl(series, :categories)
+  def categories(%Series{dtype: dtype}), do: dtype_error("categories/1", dtype, [:category])
+
   @doc """
   Creates a new dataframe with unique values and the count of each.
 
@@ -1223,6 +1282,196 @@ defmodule Explorer.Series do
   """
   def count(series), do: apply_impl(series, :count)
 
+  # List
+
+  @doc """
+  Returns the number of elements in each list of a list series.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [], nil])
+      iex> s |> Explorer.Series.list_lengths() |> Explorer.Series.to_list()
+      [2, 0, nil]
+  """
+  @spec list_lengths(series :: Series.t()) :: Series.t()
+  def list_lengths(%Series{dtype: :list} = series), do: apply_impl(series, :list_lengths)
+  def list_lengths(%Series{dtype: dtype}), do: dtype_error("list_lengths/1", dtype, [:list])
+
+  @doc """
+  Returns the element at the given index of each list of a list series. Negative indices count
+  from the end of each list. Returns nil for lists that are too short.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_get(1) |> Explorer.Series.to_list()
+      [2, nil, nil]
+      iex> s |> Explorer.Series.list_get(-1) |> Explorer.Series.to_list()
+      [2, 3, nil]
+  """
+  @spec list_get(series :: Series.t(), idx :: integer()) :: Series.t()
+  def list_get(%Series{dtype: :list} = series, idx) when is_integer(idx),
+    do: apply_impl(series, :list_get, [idx])
+
+  def list_get(%Series{dtype: dtype}, _), do: dtype_error("list_get/2", dtype, [:list])
+
+  @doc """
+  Returns a boolean series of whether each list of a list series contains the given value.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_contains(2) |> Explorer.Series.to_list()
+      [true, false, nil]
+  """
+  @spec list_contains(series :: Series.t(), value :: number() | String.t()) :: Series.t()
+  def list_contains(%Series{dtype: :list} = series, value)
+      when is_number(value) or is_binary(value),
+      do: apply_impl(series, :list_contains, [value])
+
+  def list_contains(%Series{dtype: dtype}, _), do: dtype_error("list_contains/2", dtype, [:list])
+
+  @doc """
+  Joins the elements of each list of a list series into a string. Nils are skipped.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([["a", "b"], ["c", nil], nil])
+      iex> s |> Explorer.Series.list_join("-") |> Explorer.Series.to_list()
+      ["a-b", "c", nil]
+  """
+  @spec list_join(series :: Series.t(), separator :: String.t()) :: Series.t()
+  def list_join(%Series{dtype: :list} = series, separator) when is_binary(separator),
+    do: apply_impl(series, :list_join, [separator])
+
+  def list_join(%Series{dtype: dtype}, _), do: dtype_error("list_join/2", dtype, [:list])
+
+  @doc """
+  Returns the sum of each list of a list series of numbers.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_sum() |> Explorer.Series.to_list()
+      [3, 3, nil]
+  """
+  @spec list_sum(series :: Series.t()) :: Series.t()
+  def list_sum(%Series{dtype: :list} = series), do: apply_impl(series, :list_sum)
+  def list_sum(%Series{dtype: dtype}), do: dtype_error("list_sum/1", dtype, [:list])
+
+  @doc """
+  Returns the mean of each list of a list series of numbers.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_mean() |> Explorer.Series.to_list()
+      [1.5, 3.0, nil]
+  """
+  @spec list_mean(series :: Series.t()) :: Series.t()
+  def list_mean(%Series{dtype: :list} = series), do: apply_impl(series, :list_mean)
+  def list_mean(%Series{dtype: dtype}), do: dtype_error("list_mean/1", dtype, [:list])
+
+  @doc """
+  Returns the minimum of each list of a list series of numbers or temporal values.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_min() |> Explorer.Series.to_list()
+      [1, 3, nil]
+  """
+  @spec list_min(series :: Series.t()) :: Series.t()
+  def list_min(%Series{dtype: :list} = series), do: apply_impl(series, :list_min)
+  def list_min(%Series{dtype: dtype}), do: dtype_error("list_min/1", dtype, [:list])
+
+  @doc """
+  Returns the maximum of each list of a list series of numbers or temporal values.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_max() |> Explorer.Series.to_list()
+      [2, 3, nil]
+  """
+  @spec list_max(series :: Series.t()) :: Series.t()
+  def list_max(%Series{dtype: :list} = series), do: apply_impl(series, :list_max)
+  def list_max(%Series{dtype: dtype}), do: dtype_error("list_max/1", dtype, [:list])
+
+  @doc """
+  Sorts each list of a list series.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[2, 1], [3], nil])
+      iex> s |> Explorer.Series.list_sort() |> Explorer.Series.to_list()
+      [[1, 2], [3], nil]
+      iex> s |> Explorer.Series.list_sort(true) |> Explorer.Series.to_list()
+      [[2, 1], [3], nil]
+  """
+  @spec list_sort(series :: Series.t(), reverse? :: boolean()) :: Series.t()
+  def list_sort(series, reverse? \\ false)
+
+  def list_sort(%Series{dtype: :list} = series, reverse?),
+    do: apply_impl(series, :list_sort, [reverse?])
+
+  def list_sort(%Series{dtype: dtype}, _), do: dtype_error("list_sort/2", dtype, [:list])
+
+  @doc """
+  Returns the unique values of each list of a list series.
+
+  **NB**: Does not maintain order.
+
+  ## Supported dtypes
+
+    * `:list`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([[1, 1, 2], [3], nil])
+      iex> s |> Explorer.Series.list_unique() |> Explorer.Series.list_sort() |> Explorer.Series.to_list()
+      [[1, 2], [3], nil]
+  """
+  @spec list_unique(series :: Series.t()) :: Series.t()
+  def list_unique(%Series{dtype: :list} = series), do: apply_impl(series, :list_unique)
+  def list_unique(%Series{dtype: dtype}), do: dtype_error("list_unique/1", dtype, [:list])
+
   # Rolling
 
   @doc """
@@ -1378,16 +1627,7 @@ defmodule Explorer.Series do
   end
 
   defp check_types(list) do
-    {last_item, type, types_match?} =
-      Enum.reduce_while(list, {nil, nil, true}, &check_types_reducer/2)
-
-    if not types_match?,
-      do:
-        raise(ArgumentError,
-          message:
-            "Cannot make a series from mismatched types. Type of #{inspect(last_item)} " <>
-              "does not match inferred dtype #{type}."
-        )
+    type = Explorer.Shared.check_types!(list)
 
     if is_nil(type),
       do: raise(ArgumentError, message: "cannot make a series from a list of all nils")
@@ -1395,21 +1635,19 @@ defmodule Explorer.Series do
     type
   end
 
-  defp type(item) when is_integer(item), do: :integer
-  defp type(item) when is_float(item), do: :float
-  defp type(item) when is_boolean(item), do: :boolean
-  defp type(item) when is_binary(item), do: :string
-  defp type(%Date{} = _item), do: :date
-  defp type(%NaiveDateTime{} = _item), do: :datetime
-  defp type(item) when is_nil(item), do: nil
-  defp type(item), do: raise("Unsupported datatype: #{inspect(item)}")
-
-  defp check_types_reducer(item, {_prev, type, _types_match?}) do
-    new_type = type(item) || type
-
-    if new_type != type and !is_nil(type),
-      do: {:halt, {item, type, false}},
-      else: {:cont, {item, new_type, true}}
+  defp dtype_from_options!(type, opts) do
+    case Keyword.get(opts, :dtype, type) do
+      ^type ->
+        type
+
+      :duration when type == :integer ->
+        :duration
+
+      dtype ->
+        raise(ArgumentError,
+          message: "Cannot make a series of dtype #{dtype} from values of dtype #{type}."
+        )
+    end
   end
 
   defp dtype_error(function, dtype, valid_dtypes),
@@ -16,6 +16,54 @@ defmodule Explorer.Shared do
     end
   end
 
+  @doc """
+  Infers the dtype of a list of values, or returns nil if it only holds nils.
+
+  Raises if the values are of mismatched types.
+  """
+  def check_types!(list) do
+    {last_item, type, types_match?} =
+      Enum.reduce_while(list, {nil, nil, true}, &check_types_reducer/2)
+
+    if not types_match?,
+      do:
+        raise(ArgumentError,
+          message:
+            "Cannot make a series from mismatched types. Type of #{inspect(last_item)} " <>
+              "does not match inferred dtype #{type}."
+        )
+
+    type
+  end
+
+  defp type(item) when is_integer(item), do: :integer
+  defp type(item) when is_float(item), do: :float
+  defp type(item) when is_boolean(item), do: :boolean
+  defp type(item) when is_binary(item), do: :string
+  defp type(%Date{} = _item), do: :date
+  defp type(%NaiveDateTime{} = _item), do: :datetime
+  defp type(%Time{} = _item), do: :time
+  defp type(item) when is_list(item), do: :list
+
+  defp type(item) when is_map(item) and not is_struct(item),
+    do:
+      raise(ArgumentError,
+        message:
+          "cannot make a series from maps: struct series are not supported yet, " <>
+            "build a dataframe with one column per key instead"
+      )
+
+  defp type(item) when is_nil(item), do: nil
+  defp type(item), do: raise("Unsupported datatype: #{inspect(item)}")
+
+  defp check_types_reducer(item, {_prev, type, _types_match?}) do
+    new_type = type(item) || type
+
+    if new_type != type and !is_nil(type),
+      do: {:halt, {item, type, false}},
+      else: {:cont, {item, new_type, true}}
+  end
+
   @doc """
   Gets the implementation of a dataframe or series.
   """
@@ -0,0 +1,126 @@
+use bzip2::read::BzDecoder;
+use bzip2::write::BzEncoder;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::result::Result;
+
+use crate::ExplorerError;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+pub(crate) fn compression_from_str(compression: &str) -> Result<Compression, ExplorerError> {
+    match compression {
+        "gzip" => Ok(Compression::Gzip),
+        "zstd" => Ok(Compression::Zstd),
+        "bzip2" => Ok(Compression::Bzip2),
+        c => Err(ExplorerError::Other(format!(
+            "Compression {} not supported",
+            c
+        ))),
+    }
+}
+
+impl Compression {
+    /// Detects the compression of `bytes` from its magic number.
+    pub(crate) fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Detects the compression of a file, leaving it positioned at the start.
+    pub(crate) fn detect_file(f: &mut File) -> Result<Option<Self>, ExplorerError> {
+        let mut magic = Vec::with_capacity(4);
+        f.by_ref().take(4).read_to_end(&mut magic)?;
+        f.seek(SeekFrom::Start(0))?;
+        Ok(Self::detect(&magic))
+    }
+}
+
+/// Wraps `reader` in the decoder for `compression`.
+pub(crate) fn decoder<'a, R: Read + Send + 'a>(
+    reader: R,
+    compression: Option<Compression>,
+) -> Result<Box<dyn Read + Send + 'a>, ExplorerError> {
+    Ok(match compression {
+        None => Box::new(reader),
+        Some(Compression::Gzip) => Box::new(MultiGzDecoder::new(reader)),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        Some(Compression::Bzip2) => Box::new(BzDecoder::new(reader)),
+    })
+}
+
+/// Decompresses `bytes` if they start with a known magic number, borrowing
+/// them untouched otherwise.
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Cow<[u8]>, ExplorerError> {
+    match Compression::detect(bytes) {
+        None => Ok(Cow::Borrowed(bytes)),
+        compression => {
+            let mut buf = Vec::new();
+            decoder(bytes, compression)?.read_to_end(&mut buf)?;
+            Ok(Cow::Owned(buf))
+        }
+    }
+}
+
+/// Reads a whole file, decompressing it if needed. Returns `None` for plain
+/// files so that callers can keep reading them directly.
+pub(crate) fn decompress_file(f: &mut File) -> Result<Option<Vec<u8>>, ExplorerError> {
+    match Compression::detect_file(f)? {
+        None => Ok(None),
+        compression => {
+            let mut buf = Vec::new();
+            decoder(f, compression)?.read_to_end(&mut buf)?;
+            Ok(Some(buf))
+        }
+    }
+}
+
+/// Runs `write` against `writer`, compressed with `compression`, and
+/// finishes the compressed stream.
+pub(crate) fn write_compressed<W, F>(
+    writer: W,
+    compression: Option<Compression>,
+    write: F,
+) -> Result<(), ExplorerError>
+where
+    W: Write,
+    F: FnOnce(&mut dyn Write) -> Result<(), ExplorerError>,
+{
+    match compression {
+        None => {
+            let mut writer = writer;
+            write(&mut writer)
+        }
+        Some(Compression::Gzip) => {
+            let mut encoder = GzEncoder::new(writer, flate2::Compression::default());
+            write(&mut encoder)?;
+            encoder.finish()?.flush()?;
+            Ok(())
+        }
+        Some(Compression::Zstd) => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+            write(&mut encoder)?;
+            encoder.finish()?.flush()?;
+            Ok(())
+        }
+        Some(Compression::Bzip2) => {
+            let mut encoder = BzEncoder::new(writer, bzip2::Compression::default());
+            write(&mut encoder)?;
+            encoder.finish()?.flush()?;
+            Ok(())
+        }
+    }
+}
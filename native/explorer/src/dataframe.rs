@@ -1,136 +1,2302 @@
+use polars::export::arrow::datatypes::{Schema as ArrowSchema, SchemaRef};
+use polars::export::arrow::error::ArrowError;
+use polars::export::arrow::ipc::reader::{FileReader as IpcFileReader, StreamReader};
+use polars::export::arrow::ipc::writer::StreamWriter;
+use polars::export::arrow::json::writer::record_batches_to_json_rows;
+use polars::export::arrow::record_batch::RecordBatch;
+use polars::export::chrono::format::{Item, StrftimeItems};
+use polars::export::chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use polars::prelude::*;
 
-use std::fs::File;
+use parquet::arrow::{ArrowReader, ArrowWriter, ParquetFileArrowReader};
+use parquet::basic::{Compression as BasicCompression, ConvertedType};
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use parquet::file::writer::TryClone;
+use parquet::util::cursor::{InMemoryWriteableCursor, SliceableCursor};
+
+use rayon::prelude::*;
+
+use rustler::{Binary, Env, OwnedBinary, Term};
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use std::result::Result;
+use std::sync::atomic::Ordering as AtomicOrdering;
+use std::sync::Arc;
+
+use crate::compression::{
+    compression_from_str, decoder, decompress, decompress_file, write_compressed, Compression,
+};
+use crate::series::{
+    cast_dtype_from_str, check_bounds, check_indices, datetime_dtype_from_str, is_numeric,
+    to_ex_series_collection, to_series_collection,
+};
+use crate::task::{self, Operation};
+
+use crate::{
+    ExColumnStatistics, ExCsvReadOptions, ExCsvReader, ExCsvWriteOptions, ExCsvWriter, ExDataFrame,
+    ExOperation, ExParquetMetadata, ExScalar, ExSeries, ExplorerError,
+};
 
-use crate::series::{cast, to_ex_series_collection, to_series_collection};
+macro_rules! df_read {
+    ($data: ident, $df: ident, $body: block) => {
+        match $data.resource.0.read() {
+            Ok($df) => $body,
+            Err(_) => Err(ExplorerError::Internal(
+                "Failed to take read lock for df".into(),
+            )),
+        }
+    };
+}
+
+macro_rules! df_read_read {
+    ($data: ident, $other: ident, $df: ident, $df1: ident, $body: block) => {
+        match ($data.resource.0.read(), $other.resource.0.read()) {
+            (Ok($df), Ok($df1)) => $body,
+            _ => Err(ExplorerError::Internal(
+                "Failed to take read locks for left and right".into(),
+            )),
+        }
+    };
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_csv(
+    filename: &str,
+    options: ExCsvReadOptions,
+) -> Result<ExDataFrame, ExplorerError> {
+    let options = CsvReadOptions::new(options)?;
+    let df = read_csv_file(filename, &options)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif]
+pub fn df_cancel_async(operation: ExOperation) -> bool {
+    // Returns whether this call is the one that cancelled the operation.
+    !operation.resource.0.swap(true, AtomicOrdering::Relaxed)
+}
+
+#[rustler::nif]
+pub fn df_read_csv_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    filename: String,
+    options: ExCsvReadOptions,
+) -> Result<ExOperation, ExplorerError> {
+    let options = CsvReadOptions::new(options)?;
+    Ok(task::spawn(env, reference, move |operation| {
+        let df = read_csv_file_with_progress(&filename, options, operation)?;
+        Ok(ExDataFrame::new(df))
+    }))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_csv(
+    binary: Binary,
+    options: ExCsvReadOptions,
+) -> Result<ExDataFrame, ExplorerError> {
+    let options = CsvReadOptions::new(options)?;
+    let bytes = decompress(binary.as_slice())?;
+    let df = read_csv(Cursor::new(bytes.as_ref()), &options)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_csv_batched(
+    filename: &str,
+    options: ExCsvReadOptions,
+) -> Result<ExCsvReader, ExplorerError> {
+    let options = CsvReadOptions::new(options)?;
+    let reader = CsvBatchReader::new(filename, options)?;
+    Ok(ExCsvReader::new(reader))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_next_csv_batch(
+    reader: ExCsvReader,
+    n_rows: usize,
+) -> Result<Option<ExDataFrame>, ExplorerError> {
+    match reader.resource.0.lock() {
+        Ok(mut reader) => Ok(reader.next_batch(n_rows)?.map(ExDataFrame::new)),
+        Err(_) => Err(ExplorerError::Internal(
+            "Failed to take lock for csv reader".into(),
+        )),
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct CsvReadOptions {
+    infer_schema_length: Option<usize>,
+    has_header: bool,
+    stop_after_n_rows: Option<usize>,
+    skip_rows: usize,
+    projection: Option<Vec<usize>>,
+    delimiter: u8,
+    do_rechunk: bool,
+    column_names: Option<Vec<String>>,
+    schema: Option<Schema>,
+    encoding: CsvEncoding,
+    null_values: NullValues,
+    quote_char: Option<u8>,
+    comment_char: Option<u8>,
+    skip_footer: usize,
+}
+
+impl CsvReadOptions {
+    fn new(options: ExCsvReadOptions) -> Result<Self, ExplorerError> {
+        let encoding = match options.encoding.as_str() {
+            "utf8-lossy" => CsvEncoding::LossyUtf8,
+            _ => CsvEncoding::Utf8,
+        };
+
+        let schema: Option<Schema> = match options.dtypes {
+            Some(dtypes) => Some(Schema::new(
+                dtypes
+                    .iter()
+                    .map(|(name, dtype)| Ok(Field::new(name, dtype_from_str(dtype)?)))
+                    .collect::<Result<Vec<Field>, ExplorerError>>()?,
+            )),
+            None => None,
+        };
+
+        // Per-column null values replace the single token for all columns.
+        let null_values = match options.column_null_values {
+            Some(column_null_values) => NullValues::Named(column_null_values),
+            None => NullValues::AllColumns(options.null_char),
+        };
+
+        Ok(Self {
+            infer_schema_length: options.infer_schema_length,
+            has_header: options.has_header,
+            stop_after_n_rows: options.stop_after_n_rows,
+            skip_rows: options.skip_rows,
+            projection: options.projection,
+            delimiter: first_byte(&options.delimiter, "delimiter")?,
+            do_rechunk: options.rechunk,
+            column_names: options.columns,
+            schema,
+            encoding,
+            null_values,
+            quote_char: options
+                .quote_char
+                .map(|c| first_byte(&c, "quote char"))
+                .transpose()?,
+            comment_char: options
+                .comment_char
+                .map(|c| first_byte(&c, "comment char"))
+                .transpose()?,
+            skip_footer: options.skip_footer,
+        })
+    }
+}
+
+impl Default for CsvReadOptions {
+    fn default() -> Self {
+        Self {
+            infer_schema_length: Some(1000),
+            has_header: true,
+            stop_after_n_rows: None,
+            skip_rows: 0,
+            projection: None,
+            delimiter: b',',
+            do_rechunk: true,
+            column_names: None,
+            schema: None,
+            encoding: CsvEncoding::Utf8,
+            null_values: NullValues::AllColumns("NA".into()),
+            quote_char: Some(b'"'),
+            comment_char: None,
+            skip_footer: 0,
+        }
+    }
+}
+
+fn read_csv_file(filename: &str, options: &CsvReadOptions) -> Result<DataFrame, ExplorerError> {
+    let mut f = File::open(filename)?;
+    match decompress_file(&mut f)? {
+        Some(buf) => read_csv(Cursor::new(buf), options),
+        None => read_csv(f, options),
+    }
+}
+
+// Reads the file in batches so that progress can be reported and the read
+// cancelled in between. Like the batched reader, the dtypes are inferred from
+// the first batch only.
+fn read_csv_file_with_progress(
+    filename: &str,
+    options: CsvReadOptions,
+    operation: &Operation,
+) -> Result<DataFrame, ExplorerError> {
+    let do_rechunk = options.do_rechunk;
+    let mut reader = CsvBatchReader::new(filename, options.clone())?;
+    let mut df: Option<DataFrame> = None;
+    let mut rows = 0;
+    // The first batch holds exactly the rows df_read_csv infers the dtypes
+    // from, and the batch reader keeps those dtypes for the later batches.
+    let mut batch_size = options.infer_schema_length.unwrap_or(usize::MAX).max(1);
+    while let Some(batch) = reader.next_batch(batch_size)? {
+        batch_size = CSV_READ_BATCH_SIZE;
+        rows += batch.height();
+        match df.as_mut() {
+            Some(df) => {
+                df.vstack_mut(&batch)?;
+            }
+            None => df = Some(batch),
+        }
+        operation.report(rows)?;
+    }
+
+    match df {
+        Some(mut df) => {
+            if do_rechunk {
+                df.rechunk();
+            }
+            Ok(df)
+        }
+        // Let the regular reader deal with files without any rows.
+        None => read_csv_file(filename, &options),
+    }
+}
+
+const CSV_READ_BATCH_SIZE: usize = 65536;
+
+fn read_csv<R: MmapBytesReader + Read>(
+    mut reader: R,
+    options: &CsvReadOptions,
+) -> Result<DataFrame, ExplorerError> {
+    if options.skip_footer == 0 {
+        return parse_csv(reader, options);
+    }
+
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    let end = csv_footer_start(&buf, options.skip_footer);
+    buf.truncate(end);
+    parse_csv(Cursor::new(buf), options)
+}
+
+fn parse_csv<R: MmapBytesReader>(
+    reader: R,
+    options: &CsvReadOptions,
+) -> Result<DataFrame, ExplorerError> {
+    // The CSV parser only knows primitive dtypes, so times and categoricals
+    // are read as strings and durations as integers, and converted afterwards.
+    let converted: Vec<Field> = options
+        .schema
+        .iter()
+        .flat_map(|schema| schema.fields())
+        .filter(|field| {
+            matches!(
+                field.data_type(),
+                DataType::Time64(_) | DataType::Duration(_) | DataType::Categorical
+            )
+        })
+        .cloned()
+        .collect();
+    let schema = options.schema.as_ref().map(|schema| {
+        Schema::new(
+            schema
+                .fields()
+                .iter()
+                .map(|field| match field.data_type() {
+                    DataType::Time64(_) | DataType::Categorical => {
+                        Field::new(field.name(), DataType::Utf8)
+                    }
+                    DataType::Duration(_) => Field::new(field.name(), DataType::Int64),
+                    _ => field.clone(),
+                })
+                .collect(),
+        )
+    });
+
+    let mut df = CsvReader::new(reader)
+        .infer_schema(options.infer_schema_length)
+        .has_header(options.has_header)
+        .with_stop_after_n_rows(options.stop_after_n_rows)
+        .with_delimiter(options.delimiter)
+        .with_skip_rows(options.skip_rows)
+        .with_projection(options.projection.clone())
+        .with_rechunk(options.do_rechunk)
+        .with_encoding(options.encoding)
+        .with_columns(options.column_names.clone())
+        .with_dtypes(schema.as_ref())
+        .with_null_values(Some(options.null_values.clone()))
+        .with_quote_char(options.quote_char)
+        .with_comment_char(options.comment_char)
+        .finish()?;
+
+    for field in &converted {
+        if df.find_idx_by_name(field.name()).is_some() {
+            df.may_apply(field.name(), |s| {
+                convert_parsed_column(s, field.data_type())
+            })?;
+        }
+    }
+
+    Ok(df)
+}
+
+fn convert_parsed_column(s: &Series, dtype: &DataType) -> Result<Series, PolarsError> {
+    match dtype {
+        DataType::Categorical => s.cast_with_dtype(dtype),
+        DataType::Time64(_) => {
+            let nanos = s
+                .utf8()?
+                .into_iter()
+                .map(|v| v.and_then(|v| NaiveTime::parse_from_str(v, "%H:%M:%S%.f").ok()))
+                .map(|t| {
+                    t.map(|t| {
+                        t.num_seconds_from_midnight() as i64 * 1_000_000_000 + t.nanosecond() as i64
+                    })
+                });
+            Ok(
+                ChunkedArray::<Time64NanosecondType>::new_from_opt_iter(s.name(), nanos)
+                    .into_series(),
+            )
+        }
+        DataType::Duration(TimeUnit::Millisecond) => {
+            Ok(ChunkedArray::<DurationMillisecondType>::new_from_opt_iter(
+                s.name(),
+                s.i64()?.into_iter(),
+            )
+            .into_series())
+        }
+        DataType::Duration(_) => Ok(ChunkedArray::<DurationNanosecondType>::new_from_opt_iter(
+            s.name(),
+            s.i64()?.into_iter(),
+        )
+        .into_series()),
+        _ => Ok(s.clone()),
+    }
+}
+
+// Returns the offset at which the last `skip_footer` lines of `buf` start,
+// ignoring trailing blank lines.
+fn csv_footer_start(buf: &[u8], skip_footer: usize) -> usize {
+    let mut end = buf
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |pos| pos + 1);
+    for _ in 0..skip_footer {
+        end = buf[..end].iter().rposition(|&b| b == b'\n').unwrap_or(0);
+    }
+    end
+}
+
+/// Reads a CSV file in batches of rows, re-parsing each batch with the
+/// header of the file so that every batch is a complete CSV document.
+pub struct CsvBatchReader {
+    reader: BufReader<Box<dyn Read + Send>>,
+    header: Option<Vec<u8>>,
+    // Records read ahead of the current batch, so that the last
+    // `skip_footer` records of the file are never emitted.
+    pending: VecDeque<Vec<u8>>,
+    options: CsvReadOptions,
+    rows_read: usize,
+}
+
+impl CsvBatchReader {
+    fn new(filename: &str, options: CsvReadOptions) -> Result<Self, ExplorerError> {
+        let mut f = File::open(filename)?;
+        let compression = Compression::detect_file(&mut f)?;
+        let mut reader = BufReader::new(decoder(f, compression)?);
+        let mut skipped = Vec::new();
+        for _ in 0..options.skip_rows {
+            reader.read_until(b'\n', &mut skipped)?;
+        }
+        let header = if options.has_header {
+            let mut header = Vec::new();
+            read_csv_record(&mut reader, &mut header, &options)?;
+            Some(header)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            reader,
+            header,
+            pending: VecDeque::new(),
+            options,
+            rows_read: 0,
+        })
+    }
+
+    fn next_batch(&mut self, n_rows: usize) -> Result<Option<DataFrame>, ExplorerError> {
+        let n_rows = match self.options.stop_after_n_rows {
+            Some(limit) => n_rows.min(limit.saturating_sub(self.rows_read)),
+            None => n_rows,
+        };
+
+        let mut buf = self.header.clone().unwrap_or_default();
+        let mut rows = 0;
+        while rows < n_rows {
+            match self.next_record()? {
+                Some(record) => buf.extend_from_slice(&record),
+                None => break,
+            }
+            rows += 1;
+        }
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let options = CsvReadOptions {
+            stop_after_n_rows: None,
+            skip_rows: 0,
+            skip_footer: 0,
+            ..self.options.clone()
+        };
+        let df = read_csv(Cursor::new(buf), &options)?;
+
+        // Pin the dtypes inferred from the first batch so that later batches
+        // cannot disagree with it.
+        if self.rows_read == 0 {
+            self.options.schema = Some(df.schema());
+        }
+        self.rows_read += rows;
+        Ok(Some(df))
+    }
+
+    fn next_record(&mut self) -> Result<Option<Vec<u8>>, ExplorerError> {
+        while self.pending.len() <= self.options.skip_footer {
+            let mut record = Vec::new();
+            if !read_csv_record(&mut self.reader, &mut record, &self.options)? {
+                break;
+            }
+            self.pending.push_back(record);
+        }
+        if self.pending.len() > self.options.skip_footer {
+            Ok(self.pending.pop_front())
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// Appends the next record to `buf`, skipping blank and comment lines and
+// following quoted fields across line breaks. Returns false once the reader
+// is exhausted.
+fn read_csv_record<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    options: &CsvReadOptions,
+) -> Result<bool, ExplorerError> {
+    let start = buf.len();
+    loop {
+        if reader.read_until(b'\n', buf)? == 0 {
+            return Ok(buf.len() > start);
+        }
+        let record = &buf[start..];
+        let is_comment =
+            options.comment_char.is_some() && record.first() == options.comment_char.as_ref();
+        if is_comment || record.iter().all(|b| b.is_ascii_whitespace()) {
+            buf.truncate(start);
+            continue;
+        }
+        let quotes = match options.quote_char {
+            Some(quote_char) => record.iter().filter(|&&b| b == quote_char).count(),
+            None => 0,
+        };
+        if quotes % 2 == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+fn first_byte(s: &str, option: &str) -> Result<u8, ExplorerError> {
+    s.as_bytes()
+        .first()
+        .copied()
+        .ok_or_else(|| ExplorerError::Other(format!("Expected a non-empty {}", option)))
+}
+
+fn dtype_from_str(dtype: &str) -> Result<DataType, ExplorerError> {
+    match dtype {
+        "str" => Ok(DataType::Utf8),
+        "cat" => Ok(DataType::Categorical),
+        "f32" => Ok(DataType::Float32),
+        "f64" => Ok(DataType::Float64),
+        "i8" => Ok(DataType::Int8),
+        "i16" => Ok(DataType::Int16),
+        "i32" => Ok(DataType::Int32),
+        "i64" => Ok(DataType::Int64),
+        "u8" => Ok(DataType::UInt8),
+        "u16" => Ok(DataType::UInt16),
+        "u32" => Ok(DataType::UInt32),
+        "u64" => Ok(DataType::UInt64),
+        "bool" => Ok(DataType::Boolean),
+        "date32(days)" => Ok(DataType::Date32),
+        "date64(ms)" => Ok(DataType::Date64),
+        "time64(ns)" => Ok(DataType::Time64(TimeUnit::Nanosecond)),
+        "duration(ms)" => Ok(DataType::Duration(TimeUnit::Millisecond)),
+        "duration(ns)" => Ok(DataType::Duration(TimeUnit::Nanosecond)),
+        t if t.starts_with("datetime[") => datetime_dtype_from_str(t),
+        _ => Err(ExplorerError::InvalidDtype(dtype.to_string())),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_parquet(
+    filename: &str,
+    columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    predicates: Vec<(String, String, ExScalar)>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let f = File::open(filename)?;
+    let df = read_parquet(f, columns, n_rows, predicates, None)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif]
+pub fn df_read_parquet_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    filename: String,
+    columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    predicates: Vec<(String, String, ExScalar)>,
+) -> Result<ExOperation, ExplorerError> {
+    Ok(task::spawn(env, reference, move |operation| {
+        let f = File::open(&filename)?;
+        let df = read_parquet(f, columns, n_rows, predicates, Some(operation))?;
+        Ok(ExDataFrame::new(df))
+    }))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_parquet(
+    binary: Binary,
+    columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    predicates: Vec<(String, String, ExScalar)>,
+) -> Result<ExDataFrame, ExplorerError> {
+    // The parquet reader needs an owned, 'static source, so this is the one
+    // reader that has to copy the binary.
+    let cursor = SliceableCursor::new(binary.as_slice().to_vec());
+    let df = read_parquet(cursor, columns, n_rows, predicates, None)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_dataset(
+    paths: Vec<String>,
+    format: &str,
+    csv_options: Option<ExCsvReadOptions>,
+    source_column: Option<&str>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let csv_options = match csv_options {
+        Some(options) => CsvReadOptions::new(options)?,
+        None => CsvReadOptions::default(),
+    };
+    let paths = expand_paths(paths)?;
+    let df = read_dataset(&paths, format, csv_options, source_column)?;
+    Ok(ExDataFrame::new(df))
+}
+
+// Expands every glob pattern in `paths` into the files it matches, in
+// lexicographic order. Plain paths are kept as they are.
+fn expand_paths(paths: Vec<String>) -> Result<Vec<String>, ExplorerError> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if !path.contains(|c| c == '*' || c == '?' || c == '[') {
+            expanded.push(path);
+            continue;
+        }
+        let matches = glob::glob(&path)
+            .map_err(|e| ExplorerError::Other(format!("Invalid glob {}: {}", path, e)))?;
+        let start = expanded.len();
+        for entry in matches {
+            let entry = entry.map_err(|e| ExplorerError::Io(e.into_error()))?;
+            expanded.push(entry.to_string_lossy().into_owned());
+        }
+        if expanded.len() == start {
+            return Err(ExplorerError::Other(format!(
+                "Glob {} did not match any files",
+                path
+            )));
+        }
+    }
+    Ok(expanded)
+}
+
+// Reads the files in parallel and stacks them in the order of `paths`. The
+// dtypes of each CSV file are inferred on their own and merged afterwards,
+// unless they are given in `csv_options`.
+fn read_dataset(
+    paths: &[String],
+    format: &str,
+    csv_options: CsvReadOptions,
+    source_column: Option<&str>,
+) -> Result<DataFrame, ExplorerError> {
+    let widens_to: fn(&DataType, &DataType) -> bool = match format {
+        "csv" => csv_widens_to,
+        "parquet" => widens_to,
+        f => {
+            return Err(ExplorerError::Other(format!(
+                "Dataset format {} not supported",
+                f
+            )))
+        }
+    };
+
+    let mut frames = paths
+        .par_iter()
+        .map(|path| match format {
+            "csv" => read_csv_file(path, &csv_options),
+            _ => read_parquet(File::open(path)?, None, None, vec![], None),
+        })
+        .collect::<Result<Vec<DataFrame>, ExplorerError>>()?;
+
+    if let Some(name) = source_column {
+        for (df, path) in frames.iter_mut().zip(paths) {
+            let source = Utf8Chunked::full(name, path, df.height()).into_series();
+            df.with_column(source)?;
+        }
+    }
+    stack_frames(paths, frames, widens_to)
+}
+
+fn stack_frames<P: std::fmt::Display>(
+    paths: &[P],
+    frames: Vec<DataFrame>,
+    widens_to: fn(&DataType, &DataType) -> bool,
+) -> Result<DataFrame, ExplorerError> {
+    let schema = merge_schemas(paths, &frames, widens_to)?;
+    let mut stacked: Option<DataFrame> = None;
+    for (path, df) in paths.iter().zip(frames) {
+        let df_schema = df.schema();
+        let df = cast_to_schema(df, &schema, widens_to)?.ok_or_else(|| {
+            ExplorerError::SchemaMismatch(format!(
+                "{} has schema {:?}, expected {:?}",
+                path, df_schema, schema
+            ))
+        })?;
+        match stacked.as_mut() {
+            Some(stacked) => {
+                stacked.vstack_mut(&df)?;
+            }
+            None => stacked = Some(df),
+        }
+    }
+    stacked.ok_or_else(|| ExplorerError::Other("No files to read".into()))
+}
+
+// Every column takes the widest dtype it has in any of the frames. Columns
+// that are all null, whose dtype inference could not tell, take the dtype of
+// the others.
+fn merge_schemas<P: std::fmt::Display>(
+    paths: &[P],
+    frames: &[DataFrame],
+    widens_to: fn(&DataType, &DataType) -> bool,
+) -> Result<Schema, ExplorerError> {
+    let first = frames
+        .first()
+        .ok_or_else(|| ExplorerError::Other("No files to read".into()))?;
+    let mut fields = first.schema().fields().clone();
+    let mut all_null: Vec<bool> = first.get_columns().iter().map(is_all_null).collect();
+
+    for (path, df) in paths.iter().zip(frames).skip(1) {
+        let mismatch = |fields: &[Field]| {
+            ExplorerError::SchemaMismatch(format!(
+                "{} has schema {:?}, expected {:?}",
+                path,
+                df.schema(),
+                Schema::new(fields.to_vec())
+            ))
+        };
+        if df.width() != fields.len() {
+            return Err(mismatch(&fields));
+        }
+        for (i, s) in df.get_columns().iter().enumerate() {
+            let (name, dtype) = (fields[i].name(), fields[i].data_type());
+            if s.name() != name.as_str() {
+                return Err(mismatch(&fields));
+            } else if is_all_null(s) || s.dtype() == dtype || widens_to(s.dtype(), dtype) {
+                continue;
+            } else if all_null[i] || widens_to(dtype, s.dtype()) {
+                fields[i] = Field::new(s.name(), s.dtype().clone());
+                all_null[i] = false;
+            } else {
+                return Err(mismatch(&fields));
+            }
+        }
+    }
+    Ok(Schema::new(fields))
+}
+
+fn is_all_null(s: &Series) -> bool {
+    s.null_count() == s.len()
+}
+
+// Casts the columns of `df` whose dtypes widen into the ones of `schema`, so
+// that files written with narrower dtypes can still be stacked. Returns None
+// when the column names differ or a dtype cannot be widened.
+fn cast_to_schema(
+    df: DataFrame,
+    schema: &Schema,
+    widens_to: fn(&DataType, &DataType) -> bool,
+) -> Result<Option<DataFrame>, ExplorerError> {
+    let fields = schema.fields();
+    if df.width() != fields.len() {
+        return Ok(None);
+    }
+
+    let mut columns = Vec::with_capacity(fields.len());
+    for (s, field) in df.get_columns().iter().zip(fields) {
+        if s.name() != field.name().as_str() {
+            return Ok(None);
+        } else if s.dtype() == field.data_type() {
+            columns.push(s.clone());
+        } else if is_all_null(s) {
+            columns.push(Series::full_null(s.name(), s.len(), field.data_type()));
+        } else if widens_to(s.dtype(), field.data_type()) {
+            columns.push(s.cast_with_dtype(field.data_type())?);
+        } else {
+            return Ok(None);
+        }
+    }
+    Ok(Some(DataFrame::new(columns)?))
+}
+
+// CSV dtypes are inferred per file, and a column that happens to hold only
+// whole numbers in one file is read as i64 where another file has floats.
+fn csv_widens_to(from: &DataType, to: &DataType) -> bool {
+    widens_to(from, to) || matches!((from, to), (DataType::Int64, DataType::Float64))
+}
+
+// Only the widenings that never lose precision.
+fn widens_to(from: &DataType, to: &DataType) -> bool {
+    matches!(
+        (from, to),
+        (DataType::Int8, DataType::Int16)
+            | (DataType::Int8, DataType::Int32)
+            | (DataType::Int8, DataType::Int64)
+            | (DataType::Int16, DataType::Int32)
+            | (DataType::Int16, DataType::Int64)
+            | (DataType::Int32, DataType::Int64)
+            | (DataType::UInt8, DataType::UInt16)
+            | (DataType::UInt8, DataType::UInt32)
+            | (DataType::UInt8, DataType::UInt64)
+            | (DataType::UInt16, DataType::UInt32)
+            | (DataType::UInt16, DataType::UInt64)
+            | (DataType::UInt32, DataType::UInt64)
+            | (DataType::UInt8, DataType::Int16)
+            | (DataType::UInt8, DataType::Int32)
+            | (DataType::UInt8, DataType::Int64)
+            | (DataType::UInt16, DataType::Int32)
+            | (DataType::UInt16, DataType::Int64)
+            | (DataType::UInt32, DataType::Int64)
+            | (DataType::Int8, DataType::Float32)
+            | (DataType::Int16, DataType::Float32)
+            | (DataType::UInt8, DataType::Float32)
+            | (DataType::UInt16, DataType::Float32)
+            | (DataType::Int8, DataType::Float64)
+            | (DataType::Int16, DataType::Float64)
+            | (DataType::Int32, DataType::Float64)
+            | (DataType::UInt8, DataType::Float64)
+            | (DataType::UInt16, DataType::Float64)
+            | (DataType::UInt32, DataType::Float64)
+            | (DataType::Float32, DataType::Float64)
+    )
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_write_partitioned(
+    data: ExDataFrame,
+    root: &str,
+    partition_by: Vec<String>,
+    compression: &str,
+) -> Result<(), ExplorerError> {
+    let compression = parquet_compression_from_str(compression)?;
+    df_read!(data, df, {
+        write_partitioned(&df, Path::new(root), &partition_by, compression)?;
+        Ok(())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_partitioned(
+    root: &str,
+    filters: Vec<(String, Vec<String>)>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let df = read_partitioned(Path::new(root), &filters)?;
+    Ok(ExDataFrame::new(df))
+}
+
+// The directory name Hive and Spark use for null partition keys.
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+// Writes one Parquet file per distinct combination of the `partition_by`
+// columns, under `root/key=value/...`, without the partition columns.
+fn write_partitioned(
+    df: &DataFrame,
+    root: &Path,
+    partition_by: &[String],
+    compression: BasicCompression,
+) -> Result<(), ExplorerError> {
+    if partition_by.is_empty() {
+        return Err(ExplorerError::Other(
+            "Expected at least one partition column".into(),
+        ));
+    }
+
+    // Keys are formatted like the CSV writer formats values, so that dates
+    // and datetimes are recognised again when the partitions are read.
+    let key_options = CsvWriteOptions::default();
+    let keys = partition_by
+        .iter()
+        .map(|name| format_csv_column(df.column(name)?, &key_options))
+        .collect::<Result<Vec<Vec<Option<String>>>, ExplorerError>>()?;
+    let mut data = df.clone();
+    for name in partition_by {
+        data = data.drop(name)?;
+    }
+
+    let by: Vec<&str> = partition_by.iter().map(|name| name.as_str()).collect();
+    let groupby = df.groupby(by)?;
+    for (first, idx) in groupby.get_groups() {
+        let mut dir = root.to_path_buf();
+        for (name, key) in partition_by.iter().zip(&keys) {
+            let value = match &key[*first as usize] {
+                Some(value) => escape_partition_value(value),
+                None => HIVE_DEFAULT_PARTITION.to_string(),
+            };
+            dir.push(format!("{}={}", name, value));
+        }
+        std::fs::create_dir_all(&dir)?;
+
+        let idx = UInt32Chunked::new_from_slice("idx", idx);
+        let part = data.take(&idx)?;
+        let f = File::create(dir.join("part-0.parquet"))?;
+        write_parquet(f, &part, compression, None, true, None)?;
+    }
+    Ok(())
+}
+
+// Reads every Parquet file under `root`, skipping the partitions whose key
+// values are not listed in `filters`, and restores the partition keys as
+// columns typed as integers, floats, dates, datetimes or strings.
+fn read_partitioned(
+    root: &Path,
+    filters: &[(String, Vec<String>)],
+) -> Result<DataFrame, ExplorerError> {
+    let mut files = Vec::new();
+    collect_parquet_files(root, &mut files)?;
+
+    let mut partitions = Vec::new();
+    for file in files {
+        let keys = partition_keys(root, &file)?;
+        let selected = filters.iter().all(|(name, values)| {
+            keys.iter()
+                .any(|(key, value)| key == name && values.iter().any(|v| Some(v) == value.as_ref()))
+        });
+        if selected {
+            partitions.push((file, keys));
+        }
+    }
+
+    let names: Vec<String> = match partitions.first() {
+        Some((_, keys)) => keys.iter().map(|(key, _)| key.clone()).collect(),
+        None => Vec::new(),
+    };
+    for (name, _) in filters {
+        if !names.contains(name) {
+            return Err(ExplorerError::Other(format!(
+                "{} is not a partition key",
+                name
+            )));
+        }
+    }
+    for (file, keys) in &partitions {
+        if !keys.iter().map(|(key, _)| key).eq(names.iter()) {
+            return Err(ExplorerError::SchemaMismatch(format!(
+                "Partition keys of {} do not match {:?}",
+                file.display(),
+                names
+            )));
+        }
+    }
+
+    let dtypes: Vec<DataType> = (0..names.len())
+        .map(|i| infer_partition_dtype(partitions.iter().map(|(_, keys)| &keys[i].1)))
+        .collect();
+
+    let frames = partitions
+        .par_iter()
+        .map(|(file, keys)| {
+            let mut df = read_parquet(File::open(file)?, None, None, vec![], None)?;
+            let height = df.height();
+            for ((name, value), dtype) in keys.iter().zip(&dtypes) {
+                let values = vec![value.as_deref(); height];
+                let column = Series::new(name, values.as_slice()).cast_with_dtype(dtype)?;
+                df.with_column(column)?;
+            }
+            Ok(df)
+        })
+        .collect::<Result<Vec<DataFrame>, ExplorerError>>()?;
+
+    let paths: Vec<_> = partitions.iter().map(|(file, _)| file.display()).collect();
+    stack_frames(&paths, frames, widens_to)
+}
+
+fn collect_parquet_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ExplorerError> {
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>, std::io::Error>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_parquet_files(&path, files)?;
+        } else if path.extension().map_or(false, |ext| ext == "parquet") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Parses the `key=value` directories between `root` and `file`.
+fn partition_keys(
+    root: &Path,
+    file: &Path,
+) -> Result<Vec<(String, Option<String>)>, ExplorerError> {
+    let relative = file
+        .parent()
+        .and_then(|dir| dir.strip_prefix(root).ok())
+        .ok_or_else(|| ExplorerError::Internal("Partition file outside of root".into()))?;
+
+    relative
+        .components()
+        .map(|component| {
+            let component = component.as_os_str().to_string_lossy();
+            match component.split_once('=') {
+                Some((key, HIVE_DEFAULT_PARTITION)) => Ok((key.to_string(), None)),
+                Some((key, value)) => Ok((key.to_string(), Some(unescape_partition_value(value)))),
+                None => Err(ExplorerError::Other(format!(
+                    "Expected a key=value partition directory, got {}",
+                    component
+                ))),
+            }
+        })
+        .collect()
+}
+
+// A dtype is only inferred when every key is written back unchanged by
+// write_partitioned, so that keys like "007" stay strings.
+fn infer_partition_dtype<'a, I>(values: I) -> DataType
+where
+    I: Iterator<Item = &'a Option<String>> + Clone,
+{
+    let mut present = values.flatten();
+    if present
+        .clone()
+        .all(|v| v.parse::<i64>().map_or(false, |n| n.to_string() == *v))
+    {
+        DataType::Int64
+    } else if present
+        .clone()
+        .all(|v| v.parse::<f64>().map_or(false, |f| format!("{:?}", f) == *v))
+    {
+        DataType::Float64
+    } else if present.clone().all(|v| {
+        NaiveDate::parse_from_str(v, CSV_DATE_FORMAT)
+            .map_or(false, |d| d.format(CSV_DATE_FORMAT).to_string() == *v)
+    }) {
+        DataType::Date32
+    } else if present.all(|v| {
+        v.parse::<NaiveDateTime>()
+            .map_or(false, |d| d.format(CSV_DATETIME_FORMAT).to_string() == *v)
+    }) {
+        DataType::Date64
+    } else {
+        DataType::Utf8
+    }
+}
+
+// Percent-encodes the characters Hive does not allow in partition directories.
+fn escape_partition_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_control() || "\"#%'*/:=?\\{[]^".contains(c) {
+            escaped.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+fn unescape_partition_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                unescaped.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                unescaped.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_csv_schema(
+    filename: &str,
+    infer_schema_length: Option<usize>,
+    has_header: bool,
+    sep: &str,
+) -> Result<Vec<(String, String)>, ExplorerError> {
+    // Only the rows used for inference are read, streaming through compressed
+    // files, so the dtypes match what df_read_csv would produce with the same
+    // options. Without a limit, inference stops after CSV_SCHEMA_MAX_ROWS.
+    let rows = infer_schema_length.unwrap_or(CSV_SCHEMA_MAX_ROWS);
+    let options = CsvReadOptions {
+        infer_schema_length: Some(rows),
+        has_header,
+        delimiter: first_byte(sep, "delimiter")?,
+        ..CsvReadOptions::default()
+    };
+    let mut reader = CsvBatchReader::new(filename, options.clone())?;
+    let df = match reader.next_batch(rows)? {
+        Some(df) => df,
+        // Files without records only have the column names of their header.
+        None => read_csv(
+            Cursor::new(reader.header.take().unwrap_or_default()),
+            &options,
+        )?,
+    };
+    Ok(schema_to_list(&df.schema()))
+}
+
+const CSV_SCHEMA_MAX_ROWS: usize = 10_000;
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_metadata(filename: &str) -> Result<ExParquetMetadata, ExplorerError> {
+    let file_reader = SerializedFileReader::new(File::open(filename)?)?;
+    let metadata = file_reader.metadata();
+    let row_groups = metadata.row_groups();
+
+    let statistics = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            let mut min: Option<ExScalar> = None;
+            let mut max: Option<ExScalar> = None;
+            let mut null_count = Some(0);
+            for rg in row_groups {
+                match rg.column(idx).statistics() {
+                    Some(stats) => {
+                        null_count = null_count.map(|n| n + stats.null_count());
+                        if let Some((rg_min, rg_max)) =
+                            statistics_min_max(stats, column.converted_type())
+                        {
+                            if min.as_ref().map_or(true, |min| rg_min < *min) {
+                                min = Some(rg_min);
+                            }
+                            if max.as_ref().map_or(true, |max| rg_max > *max) {
+                                max = Some(rg_max);
+                            }
+                        }
+                    }
+                    None => null_count = None,
+                }
+            }
+            ExColumnStatistics {
+                name: column.name().to_string(),
+                min,
+                max,
+                null_count,
+            }
+        })
+        .collect();
+    let num_rows = metadata.file_metadata().num_rows();
+    let num_row_groups = row_groups.len();
+
+    let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+    let schema = arrow_reader.get_schema()?;
+    let schema = Schema::new(schema.fields().iter().map(Field::from).collect());
+
+    Ok(ExParquetMetadata {
+        schema: schema_to_list(&schema),
+        num_rows,
+        num_row_groups,
+        statistics,
+    })
+}
+
+fn schema_to_list(schema: &Schema) -> Vec<(String, String)> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().to_string(), field.data_type().to_string()))
+        .collect()
+}
+
+const PARQUET_BATCH_SIZE: usize = 65536;
+
+fn read_parquet<R: ChunkReader + 'static>(
+    reader: R,
+    columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    predicates: Vec<(String, String, ExScalar)>,
+    operation: Option<&Operation>,
+) -> Result<DataFrame, ExplorerError> {
+    for (_, op, _) in &predicates {
+        if !matches!(op.as_str(), "eq" | "lt" | "lt_eq" | "gt" | "gt_eq") {
+            return Err(ExplorerError::Other(format!(
+                "Predicate operator {} not supported",
+                op
+            )));
+        }
+    }
+
+    let mut file_reader = SerializedFileReader::new(reader)?;
+    let schema_descr = file_reader.metadata().file_metadata().schema_descr_ptr();
+    let column_idx = |name: &str| {
+        schema_descr
+            .columns()
+            .iter()
+            .position(|c| c.name() == name)
+            .ok_or_else(|| ExplorerError::ColumnNotFound(name.to_string()))
+    };
+
+    let predicate_idx = predicates
+        .iter()
+        .map(|(name, _, _)| column_idx(name))
+        .collect::<Result<Vec<usize>, ExplorerError>>()?;
+
+    // Drop the row groups whose min/max statistics prove that no row can
+    // satisfy every predicate, so they are never decoded.
+    file_reader.filter_row_groups(&|rg: &RowGroupMetaData, _| {
+        predicates
+            .iter()
+            .zip(&predicate_idx)
+            .all(|((_, op, value), &idx)| row_group_may_match(rg, idx, op, value))
+    });
+
+    // Predicate columns have to be read even when they are not selected, and
+    // are dropped again once the predicates have been applied.
+    let read_columns = columns.clone().map(|mut read_columns| {
+        for (name, _, _) in &predicates {
+            if !read_columns.contains(name) {
+                read_columns.push(name.clone());
+            }
+        }
+        read_columns
+    });
+
+    let projection = match &read_columns {
+        Some(read_columns) => read_columns
+            .iter()
+            .map(|name| column_idx(name))
+            .collect::<Result<Vec<usize>, ExplorerError>>()?,
+        None => (0..schema_descr.num_columns()).collect(),
+    };
+
+    let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+    let batch_reader = arrow_reader.get_record_reader_by_columns(projection, PARQUET_BATCH_SIZE)?;
+
+    let mut batches = Vec::new();
+    let mut rows = 0;
+    for batch in batch_reader {
+        let batch = batch?;
+        rows += batch.num_rows();
+        batches.push(batch);
+        if let Some(operation) = operation {
+            operation.report(rows)?;
+        }
+        // Predicates are applied after decoding, so the row limit can only
+        // stop the read early when there are none.
+        if predicates.is_empty() && n_rows.map_or(false, |n| rows >= n) {
+            break;
+        }
+    }
+
+    let mut df = if batches.is_empty() {
+        let read_columns = read_columns.unwrap_or_else(|| {
+            schema_descr
+                .columns()
+                .iter()
+                .map(|c| c.name().to_string())
+                .collect()
+        });
+        let schema = arrow_reader.get_schema()?;
+        let cols = read_columns
+            .iter()
+            .map(|name| {
+                let field = Field::from(schema.field_with_name(name)?);
+                Ok(Series::full_null(name, 0, field.data_type()))
+            })
+            .collect::<Result<Vec<Series>, ExplorerError>>()?;
+        DataFrame::new(cols)?
+    } else {
+        DataFrame::try_from(batches)?
+    };
+
+    for (name, op, value) in &predicates {
+        let mask = compare_scalar(df.column(name)?, op, value)?;
+        df = df.filter(&mask)?;
+    }
+    if let Some(columns) = &columns {
+        df = df.select(columns)?;
+    }
+
+    Ok(match n_rows {
+        Some(_) => df.head(n_rows),
+        None => df,
+    })
+}
+
+fn row_group_may_match(rg: &RowGroupMetaData, idx: usize, op: &str, value: &ExScalar) -> bool {
+    let column = rg.column(idx);
+    let converted_type = column.column_descr().converted_type();
+    let min_max = column
+        .statistics()
+        .and_then(|stats| statistics_min_max(stats, converted_type));
+    let (min, max) = match min_max {
+        Some(min_max) => min_max,
+        None => return true,
+    };
+    let (min_cmp, max_cmp) = match (min.partial_cmp(value), max.partial_cmp(value)) {
+        (Some(min_cmp), Some(max_cmp)) => (min_cmp, max_cmp),
+        _ => return true,
+    };
+    match op {
+        "eq" => min_cmp != Ordering::Greater && max_cmp != Ordering::Less,
+        "lt" => min_cmp == Ordering::Less,
+        "lt_eq" => min_cmp != Ordering::Greater,
+        "gt" => max_cmp == Ordering::Greater,
+        "gt_eq" => max_cmp != Ordering::Less,
+        _ => true,
+    }
+}
+
+// Unsigned integers, dates and times share the physical integer types with
+// plain signed integers, so only the latter are compared as i64. Returning
+// None keeps the row group.
+fn statistics_min_max(
+    stats: &Statistics,
+    converted_type: ConvertedType,
+) -> Option<(ExScalar, ExScalar)> {
+    if !stats.has_min_max_set() {
+        return None;
+    }
+    let signed = matches!(
+        converted_type,
+        ConvertedType::NONE
+            | ConvertedType::INT_8
+            | ConvertedType::INT_16
+            | ConvertedType::INT_32
+            | ConvertedType::INT_64
+    );
+    match stats {
+        Statistics::Int32(s) if signed => Some((
+            ExScalar::Int(*s.min() as i64),
+            ExScalar::Int(*s.max() as i64),
+        )),
+        Statistics::Int64(s) if signed => Some((ExScalar::Int(*s.min()), ExScalar::Int(*s.max()))),
+        Statistics::Float(s) => Some((
+            ExScalar::Float(*s.min() as f64),
+            ExScalar::Float(*s.max() as f64),
+        )),
+        Statistics::Double(s) => Some((ExScalar::Float(*s.min()), ExScalar::Float(*s.max()))),
+        Statistics::ByteArray(s) => Some((
+            ExScalar::Str(s.min().as_utf8().ok()?.to_string()),
+            ExScalar::Str(s.max().as_utf8().ok()?.to_string()),
+        )),
+        _ => None,
+    }
+}
+
+macro_rules! compare {
+    ($s: ident, $op: ident, $value: expr) => {
+        match $op {
+            "eq" => Ok($s.eq($value)),
+            "lt" => Ok($s.lt($value)),
+            "lt_eq" => Ok($s.lt_eq($value)),
+            "gt" => Ok($s.gt($value)),
+            "gt_eq" => Ok($s.gt_eq($value)),
+            op => Err(ExplorerError::Other(format!(
+                "Predicate operator {} not supported",
+                op
+            ))),
+        }
+    };
+}
+
+// polars compares series of the wrong dtype without complaining, so the
+// scalar is checked against the dtype first. Dates compare as their integer
+// representation.
+pub(crate) fn compare_scalar(
+    s: &Series,
+    op: &str,
+    value: &ExScalar,
+) -> Result<BooleanChunked, ExplorerError> {
+    match (value, s.dtype()) {
+        (ExScalar::Int(v), DataType::Date32 | DataType::Date64) => compare!(s, op, *v),
+        (ExScalar::Int(v), dt) if is_numeric(dt) => compare!(s, op, *v),
+        (ExScalar::Float(v), dt) if is_numeric(dt) => compare!(s, op, *v),
+        (ExScalar::Str(v), DataType::Utf8) => compare!(s, op, v.as_str()),
+        (_, dt) => Err(ExplorerError::InvalidDtype(format!(
+            "cannot compare column {} of dtype {:?} with {:?}",
+            s.name(),
+            dt,
+            value
+        ))),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_write_parquet(
+    data: ExDataFrame,
+    filename: &str,
+    compression: &str,
+    row_group_size: Option<usize>,
+    statistics: bool,
+) -> Result<(), ExplorerError> {
+    let compression = parquet_compression_from_str(compression)?;
+    df_read!(data, df, {
+        let f = File::create(filename)?;
+        write_parquet(f, &df, compression, row_group_size, statistics, None)?;
+        Ok(())
+    })
+}
+
+#[rustler::nif]
+pub fn df_write_parquet_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    data: ExDataFrame,
+    filename: String,
+    compression: &str,
+    row_group_size: Option<usize>,
+    statistics: bool,
+) -> Result<ExOperation, ExplorerError> {
+    let compression = parquet_compression_from_str(compression)?;
+    Ok(task::spawn(env, reference, move |operation| {
+        df_read!(data, df, {
+            let f = File::create(&filename)?;
+            write_parquet(
+                f,
+                &df,
+                compression,
+                row_group_size,
+                statistics,
+                Some(operation),
+            )
+        })
+    }))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_parquet<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+    compression: &str,
+    row_group_size: Option<usize>,
+    statistics: bool,
+) -> Result<Binary<'a>, ExplorerError> {
+    let compression = parquet_compression_from_str(compression)?;
+    df_read!(data, df, {
+        let cursor = InMemoryWriteableCursor::default();
+        write_parquet(
+            cursor.clone(),
+            &df,
+            compression,
+            row_group_size,
+            statistics,
+            None,
+        )?;
+        let bytes = cursor.into_inner().ok_or_else(|| {
+            ExplorerError::Internal("Parquet writer still holds the buffer".into())
+        })?;
+        to_binary(env, &bytes)
+    })
+}
+
+// Drives the arrow writer one row group at a time, rather than polars'
+// ParquetWriter, so that the async writer can report progress in between.
+fn write_parquet<W: 'static + Write + Seek + TryClone>(
+    writer: W,
+    df: &DataFrame,
+    compression: BasicCompression,
+    row_group_size: Option<usize>,
+    statistics: bool,
+    operation: Option<&Operation>,
+) -> Result<(), ExplorerError> {
+    let properties = WriterProperties::builder()
+        .set_compression(compression)
+        .set_statistics_enabled(statistics)
+        .build();
+    let row_group_size = row_group_size.unwrap_or(PARQUET_BATCH_SIZE).max(1);
+    let schema = Arc::new(df.schema().to_arrow());
+    let mut writer = ArrowWriter::try_new(writer, schema, Some(properties))?;
+    let mut offset = 0;
+    while offset < df.height() {
+        // Every record batch is written as its own row group.
+        let mut row_group = df.slice(offset as i64, row_group_size);
+        row_group.rechunk();
+        for batch in row_group.as_record_batches()? {
+            writer.write(&batch)?;
+        }
+        offset += row_group.height();
+        if let Some(operation) = operation {
+            operation.report(offset)?;
+        }
+    }
+    writer.close()?;
+    Ok(())
+}
+
+fn to_binary<'a>(env: Env<'a>, bytes: &[u8]) -> Result<Binary<'a>, ExplorerError> {
+    let mut binary = OwnedBinary::new(bytes.len())
+        .ok_or_else(|| ExplorerError::Internal("Failed to allocate binary".into()))?;
+    binary.as_mut_slice().copy_from_slice(bytes);
+    Ok(binary.release(env))
+}
+
+fn parquet_compression_from_str(compression: &str) -> Result<BasicCompression, ExplorerError> {
+    match compression {
+        "uncompressed" => Ok(BasicCompression::UNCOMPRESSED),
+        "snappy" => Ok(BasicCompression::SNAPPY),
+        "gzip" => Ok(BasicCompression::GZIP),
+        "zstd" => Ok(BasicCompression::ZSTD),
+        "lz4" => Ok(BasicCompression::LZ4),
+        c => Err(ExplorerError::Other(format!(
+            "Parquet compression {} not supported",
+            c
+        ))),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_ipc(
+    filename: &str,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let f = File::open(filename)?;
+    let df = read_ipc(f, columns)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_ipc_stream(
+    filename: &str,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let f = File::open(filename)?;
+    let df = read_ipc_stream(f, columns)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ipc(
+    binary: Binary,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let df = read_ipc(Cursor::new(binary.as_slice()), columns)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ipc_stream(
+    binary: Binary,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let df = read_ipc_stream(binary.as_slice(), columns)?;
+    Ok(ExDataFrame::new(df))
+}
 
-use crate::{ExDataFrame, ExSeries, ExplorerError};
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_write_ipc(data: ExDataFrame, filename: &str) -> Result<(), ExplorerError> {
+    df_read!(data, df, {
+        let f = File::create(filename)?;
+        IpcWriter::new(f).finish(&mut df.clone())?;
+        Ok(())
+    })
+}
 
-macro_rules! df_read {
-    ($data: ident, $df: ident, $body: block) => {
-        match $data.resource.0.read() {
-            Ok($df) => $body,
-            Err(_) => Err(ExplorerError::Internal(
-                "Failed to take read lock for df".into(),
-            )),
-        }
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_write_ipc_stream(data: ExDataFrame, filename: &str) -> Result<(), ExplorerError> {
+    df_read!(data, df, {
+        let f = File::create(filename)?;
+        write_ipc_stream(f, &df)?;
+        Ok(())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc<'a>(env: Env<'a>, data: ExDataFrame) -> Result<Binary<'a>, ExplorerError> {
+    df_read!(data, df, {
+        let mut buf: Vec<u8> = Vec::new();
+        IpcWriter::new(&mut buf).finish(&mut df.clone())?;
+        to_binary(env, &buf)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc_stream<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+) -> Result<Binary<'a>, ExplorerError> {
+    df_read!(data, df, {
+        let mut buf: Vec<u8> = Vec::new();
+        write_ipc_stream(&mut buf, &df)?;
+        to_binary(env, &buf)
+    })
+}
+
+fn read_ipc<R: Read + Seek>(
+    reader: R,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, ExplorerError> {
+    let file_reader = IpcFileReader::try_new(reader)?;
+    let schema = file_reader.schema();
+    project_ipc_batches(schema, file_reader, columns)
+}
+
+fn read_ipc_stream<R: Read>(
+    reader: R,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, ExplorerError> {
+    let stream_reader = StreamReader::try_new(reader)?;
+    let schema = stream_reader.schema();
+    project_ipc_batches(schema, stream_reader, columns)
+}
+
+// The bundled Arrow IPC readers always decode every column of a batch, so the
+// columns that are not selected are dropped batch by batch, before anything
+// is converted to polars, rather than after reading the whole file.
+fn project_ipc_batches<I>(
+    schema: SchemaRef,
+    batches: I,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, ExplorerError>
+where
+    I: Iterator<Item = Result<RecordBatch, ArrowError>>,
+{
+    let indices = columns
+        .map(|columns| {
+            columns
+                .iter()
+                .map(|name| {
+                    schema
+                        .index_of(name)
+                        .map_err(|_| ExplorerError::ColumnNotFound(name.clone()))
+                })
+                .collect::<Result<Vec<usize>, ExplorerError>>()
+        })
+        .transpose()?;
+    let schema = match &indices {
+        Some(indices) => Arc::new(ArrowSchema::new(
+            indices.iter().map(|&i| schema.field(i).clone()).collect(),
+        )),
+        None => schema,
     };
+
+    let batches = batches
+        .map(|batch| match &indices {
+            Some(indices) => batch.and_then(|batch| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    indices.iter().map(|&i| batch.column(i).clone()).collect(),
+                )
+            }),
+            None => batch,
+        })
+        .collect::<Result<Vec<RecordBatch>, ArrowError>>()?;
+
+    if batches.is_empty() {
+        let cols = schema
+            .fields()
+            .iter()
+            .map(|f| {
+                let field = Field::from(f);
+                Series::full_null(field.name(), 0, field.data_type())
+            })
+            .collect();
+        Ok(DataFrame::new(cols)?)
+    } else {
+        Ok(DataFrame::try_from(batches)?)
+    }
 }
 
-macro_rules! df_read_read {
-    ($data: ident, $other: ident, $df: ident, $df1: ident, $body: block) => {
-        match ($data.resource.0.read(), $other.resource.0.read()) {
-            (Ok($df), Ok($df1)) => $body,
-            _ => Err(ExplorerError::Internal(
-                "Failed to take read locks for left and right".into(),
-            )),
+fn write_ipc_stream<W: Write>(writer: W, df: &DataFrame) -> Result<(), ExplorerError> {
+    let schema = df.schema().to_arrow();
+    let mut stream_writer = StreamWriter::try_new(writer, &schema)?;
+    for batch in df.as_record_batches()? {
+        stream_writer.write(&batch)?;
+    }
+    stream_writer.finish()?;
+    Ok(())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_to_csv(data: ExDataFrame, options: ExCsvWriteOptions) -> Result<String, ExplorerError> {
+    let options = CsvWriteOptions::new(options)?;
+    df_read!(data, df, {
+        let mut buf: Vec<u8> = Vec::with_capacity(81920);
+        write_csv(&mut buf, &df, &options, None)?;
+
+        let s = String::from_utf8(buf)?;
+        Ok(s)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_csv_file(
+    data: ExDataFrame,
+    filename: &str,
+    options: ExCsvWriteOptions,
+    compression: Option<&str>,
+) -> Result<(), ExplorerError> {
+    let options = CsvWriteOptions::new(options)?;
+    df_read!(data, df, {
+        let compression = compression.map(compression_from_str).transpose()?;
+        let f = File::create(filename)?;
+        write_compressed(BufWriter::new(f), compression, |writer| {
+            write_csv(writer, &df, &options, None)
+        })?;
+        Ok(())
+    })
+}
+
+#[rustler::nif]
+pub fn df_to_csv_file_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    data: ExDataFrame,
+    filename: String,
+    options: ExCsvWriteOptions,
+    compression: Option<&str>,
+) -> Result<ExOperation, ExplorerError> {
+    let options = CsvWriteOptions::new(options)?;
+    let compression = compression.map(compression_from_str).transpose()?;
+    Ok(task::spawn(env, reference, move |operation| {
+        df_read!(data, df, {
+            let f = File::create(&filename)?;
+            write_compressed(BufWriter::new(f), compression, |writer| {
+                write_csv(writer, &df, &options, Some(operation))
+            })?;
+            Ok(())
+        })
+    }))
+}
+
+enum QuoteStyle {
+    Necessary,
+    Always,
+    NonNumeric,
+    Never,
+}
+
+pub(crate) struct CsvWriteOptions {
+    has_headers: bool,
+    delimiter: u8,
+    quote_style: QuoteStyle,
+    null_value: String,
+    date_format: String,
+    datetime_format: String,
+    float_precision: Option<usize>,
+}
+
+impl CsvWriteOptions {
+    fn new(options: ExCsvWriteOptions) -> Result<Self, ExplorerError> {
+        let quote_style = match options.quote_style.as_str() {
+            "necessary" => QuoteStyle::Necessary,
+            "always" => QuoteStyle::Always,
+            "non_numeric" => QuoteStyle::NonNumeric,
+            "never" => QuoteStyle::Never,
+            s => {
+                return Err(ExplorerError::Other(format!(
+                    "Quote style {} not supported",
+                    s
+                )))
+            }
+        };
+
+        let date_format = options
+            .date_format
+            .unwrap_or_else(|| CSV_DATE_FORMAT.into());
+        let datetime_format = options
+            .datetime_format
+            .unwrap_or_else(|| CSV_DATETIME_FORMAT.into());
+        check_strftime_format(&date_format)?;
+        check_strftime_format(&datetime_format)?;
+
+        Ok(Self {
+            has_headers: options.has_headers,
+            delimiter: options.delimiter,
+            quote_style,
+            null_value: options.null_value,
+            date_format,
+            datetime_format,
+            float_precision: options.float_precision,
+        })
+    }
+}
+
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        Self {
+            has_headers: true,
+            delimiter: b',',
+            quote_style: QuoteStyle::Necessary,
+            null_value: String::new(),
+            date_format: CSV_DATE_FORMAT.into(),
+            datetime_format: CSV_DATETIME_FORMAT.into(),
+            float_precision: None,
+        }
+    }
+}
+
+const CSV_DATE_FORMAT: &str = "%Y-%m-%d";
+const CSV_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f";
+const CSV_WRITE_CHUNK_SIZE: usize = 8192;
+
+fn write_csv<W: Write>(
+    mut writer: W,
+    df: &DataFrame,
+    options: &CsvWriteOptions,
+    operation: Option<&Operation>,
+) -> Result<(), ExplorerError> {
+    if options.has_headers {
+        let names = df.get_column_names();
+        for (i, name) in names.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(&[options.delimiter])?;
+            }
+            write_csv_field(&mut writer, name, false, options)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    let numeric: Vec<bool> = df.dtypes().iter().map(is_numeric).collect();
+    let mut offset = 0;
+    while offset < df.height() {
+        let chunk = df.slice(offset as i64, CSV_WRITE_CHUNK_SIZE);
+        let columns = chunk
+            .get_columns()
+            .iter()
+            .map(|s| format_csv_column(s, options))
+            .collect::<Result<Vec<Vec<Option<String>>>, ExplorerError>>()?;
+
+        for row in 0..chunk.height() {
+            for (i, column) in columns.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(&[options.delimiter])?;
+                }
+                match &column[row] {
+                    Some(value) => write_csv_field(&mut writer, value, numeric[i], options)?,
+                    None => writer.write_all(options.null_value.as_bytes())?,
+                }
+            }
+            writer.write_all(b"\n")?;
+        }
+        offset += chunk.height();
+        if let Some(operation) = operation {
+            operation.report(offset)?;
         }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_csv_field<W: Write>(
+    writer: &mut W,
+    value: &str,
+    numeric: bool,
+    options: &CsvWriteOptions,
+) -> Result<(), ExplorerError> {
+    let quote = match options.quote_style {
+        QuoteStyle::Always => true,
+        QuoteStyle::Never => false,
+        QuoteStyle::NonNumeric => !numeric,
+        QuoteStyle::Necessary => value
+            .bytes()
+            .any(|b| b == options.delimiter || b == b'"' || b == b'\n' || b == b'\r'),
     };
+
+    if quote {
+        writer.write_all(b"\"")?;
+        writer.write_all(value.replace('"', "\"\"").as_bytes())?;
+        writer.write_all(b"\"")?;
+    } else {
+        writer.write_all(value.as_bytes())?;
+    }
+    Ok(())
 }
 
-#[rustler::nif]
-pub fn df_read_csv(
+fn format_csv_column(
+    s: &Series,
+    options: &CsvWriteOptions,
+) -> Result<Vec<Option<String>>, ExplorerError> {
+    let values = match s.dtype() {
+        DataType::Float32 => s
+            .f32()?
+            .into_iter()
+            .map(|v| v.map(|v| format_float(v, options.float_precision)))
+            .collect(),
+        DataType::Float64 => s
+            .f64()?
+            .into_iter()
+            .map(|v| v.map(|v| format_float(v, options.float_precision)))
+            .collect(),
+        DataType::Date32 => s
+            .date32()?
+            .into_iter()
+            .map(|v| {
+                v.map(|days| {
+                    let date = NaiveDate::from_num_days_from_ce_opt(days + EPOCH_DAYS_FROM_CE)
+                        .ok_or_else(|| out_of_range("date", days))?;
+                    format_temporal(date.format(&options.date_format))
+                })
+                .transpose()
+            })
+            .collect::<Result<_, ExplorerError>>()?,
+        DataType::Date64 => s
+            .date64()?
+            .into_iter()
+            .map(|v| {
+                v.map(|ms| {
+                    let datetime = NaiveDateTime::from_timestamp_opt(
+                        ms.div_euclid(1000),
+                        (ms.rem_euclid(1000) * 1_000_000) as u32,
+                    )
+                    .ok_or_else(|| out_of_range("datetime", ms))?;
+                    format_temporal(datetime.format(&options.datetime_format))
+                })
+                .transpose()
+            })
+            .collect::<Result<_, ExplorerError>>()?,
+        DataType::Time64(_) => s
+            .cast::<Int64Type>()?
+            .i64()?
+            .into_iter()
+            .map(|v| {
+                v.map(|ns| {
+                    let time = NaiveTime::from_num_seconds_from_midnight_opt(
+                        ns.div_euclid(1_000_000_000) as u32,
+                        ns.rem_euclid(1_000_000_000) as u32,
+                    )
+                    .ok_or_else(|| out_of_range("time", ns))?;
+                    format_temporal(time.format("%H:%M:%S%.f"))
+                })
+                .transpose()
+            })
+            .collect::<Result<_, ExplorerError>>()?,
+        DataType::Duration(_) => s
+            .cast::<Int64Type>()?
+            .i64()?
+            .into_iter()
+            .map(|v| v.map(|v| v.to_string()))
+            .collect(),
+        _ => s
+            .cast::<Utf8Type>()?
+            .utf8()?
+            .into_iter()
+            .map(|v| v.map(|v| v.to_string()))
+            .collect(),
+    };
+    Ok(values)
+}
+
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+// f32 values are printed as f32, so that 0.1 does not turn into the f64
+// 0.10000000149011612.
+fn format_float<F: fmt::Debug + fmt::Display>(v: F, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, v),
+        None => format!("{:?}", v),
+    }
+}
+
+// chrono reports a format it cannot apply, such as a time in a date format,
+// as a fmt::Error, which to_string would turn into a panic.
+fn format_temporal<D: fmt::Display>(value: D) -> Result<String, ExplorerError> {
+    let mut out = String::new();
+    fmt::Write::write_fmt(&mut out, format_args!("{}", value))
+        .map_err(|_| ExplorerError::Other("Invalid date or datetime format".into()))?;
+    Ok(out)
+}
+
+fn out_of_range<V: fmt::Display>(dtype: &str, value: V) -> ExplorerError {
+    ExplorerError::OutOfBounds(format!("{} value {} is out of range", dtype, value))
+}
+
+fn check_strftime_format(format: &str) -> Result<(), ExplorerError> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        Err(ExplorerError::Other(format!(
+            "Invalid date or datetime format {}",
+            format
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_open_csv_writer(
+    filename: &str,
+    options: ExCsvWriteOptions,
+    append: bool,
+) -> Result<ExCsvWriter, ExplorerError> {
+    let options = CsvWriteOptions::new(options)?;
+    let writer = CsvBatchWriter::new(filename, options, append)?;
+    Ok(ExCsvWriter::new(writer))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_write_csv_batch(writer: ExCsvWriter, data: ExDataFrame) -> Result<(), ExplorerError> {
+    match writer.resource.0.lock() {
+        Ok(mut writer) => match writer.as_mut() {
+            Some(writer) => df_read!(data, df, { writer.write_batch(&df) }),
+            None => Err(ExplorerError::Other("CSV writer is closed".into())),
+        },
+        Err(_) => Err(ExplorerError::Internal(
+            "Failed to take lock for csv writer".into(),
+        )),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_close_csv_writer(writer: ExCsvWriter) -> Result<(), ExplorerError> {
+    match writer.resource.0.lock() {
+        Ok(mut writer) => match writer.take() {
+            Some(mut writer) => Ok(writer.writer.flush()?),
+            None => Ok(()),
+        },
+        Err(_) => Err(ExplorerError::Internal(
+            "Failed to take lock for csv writer".into(),
+        )),
+    }
+}
+
+/// Writes successive frames with the same schema to one CSV file, emitting
+/// the header only once.
+pub struct CsvBatchWriter {
+    writer: BufWriter<File>,
+    // has_headers is cleared once the header has been written, or when
+    // appending to a file that already has one.
+    options: CsvWriteOptions,
+    // Column names already present in the file, taken from its header when
+    // appending to a non-empty file.
+    columns: Option<Vec<String>>,
+    schema: Option<Schema>,
+}
+
+impl CsvBatchWriter {
+    fn new(
+        filename: &str,
+        mut options: CsvWriteOptions,
+        append: bool,
+    ) -> Result<Self, ExplorerError> {
+        let columns = if append && options.has_headers {
+            match File::open(filename) {
+                Ok(f) => {
+                    let mut header = String::new();
+                    BufReader::new(f).read_line(&mut header)?;
+                    if header.trim_end().is_empty() {
+                        None
+                    } else {
+                        // Parsed like df_read_csv would, so that quoted names
+                        // may hold the delimiter.
+                        let read_options = CsvReadOptions {
+                            has_header: true,
+                            delimiter: options.delimiter,
+                            ..CsvReadOptions::default()
+                        };
+                        let df = read_csv(Cursor::new(header.into_bytes()), &read_options)?;
+                        Some(
+                            df.get_column_names()
+                                .iter()
+                                .map(|name| name.to_string())
+                                .collect(),
+                        )
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            None
+        };
+        if columns.is_some() {
+            options.has_headers = false;
+        }
+
+        let f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(filename)?;
+
+        Ok(Self {
+            writer: BufWriter::new(f),
+            options,
+            columns,
+            schema: None,
+        })
+    }
+
+    fn write_batch(&mut self, df: &DataFrame) -> Result<(), ExplorerError> {
+        let schema = df.schema();
+        match &self.schema {
+            Some(expected) if *expected != schema => {
+                return Err(ExplorerError::SchemaMismatch(format!(
+                    "expected {:?}, got {:?}",
+                    expected, schema
+                )))
+            }
+            Some(_) => (),
+            None => {
+                if let Some(columns) = &self.columns {
+                    let names = df.get_column_names();
+                    if *columns != names {
+                        return Err(ExplorerError::SchemaMismatch(format!(
+                            "file has columns {:?}, got {:?}",
+                            columns, names
+                        )));
+                    }
+                }
+            }
+        }
+
+        write_csv(&mut self.writer, df, &self.options, None)?;
+        self.options.has_headers = false;
+        self.schema = Some(schema);
+        Ok(())
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_ndjson(
     filename: &str,
     infer_schema_length: Option<usize>,
-    has_header: bool,
-    stop_after_n_rows: Option<usize>,
-    skip_rows: usize,
-    projection: Option<Vec<usize>>,
-    sep: &str,
-    do_rechunk: bool,
-    column_names: Option<Vec<String>>,
     dtypes: Option<Vec<(&str, &str)>>,
-    encoding: &str,
-    null_char: String,
+    columns: Option<Vec<String>>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let encoding = match encoding {
-        "utf8-lossy" => CsvEncoding::LossyUtf8,
-        _ => CsvEncoding::Utf8,
-    };
-
-    let schema: Option<Schema> = if let Some(dtypes) = dtypes {
-        Some(Schema::new(
-            dtypes
-                .iter()
-                .map(|x| Field::new(x.0, dtype_from_str(x.1).unwrap()))
-                .collect(),
-        ))
-    } else {
-        None
+    let mut f = File::open(filename)?;
+    let df = match decompress_file(&mut f)? {
+        Some(buf) => read_ndjson(Cursor::new(buf), infer_schema_length, dtypes, columns)?,
+        None => read_ndjson(f, infer_schema_length, dtypes, columns)?,
     };
+    Ok(ExDataFrame::new(df))
+}
 
-    let df = CsvReader::from_path(filename)?
-        .infer_schema(infer_schema_length)
-        .has_header(has_header)
-        .with_stop_after_n_rows(stop_after_n_rows)
-        .with_delimiter(sep.as_bytes()[0])
-        .with_skip_rows(skip_rows)
-        .with_projection(projection)
-        .with_rechunk(do_rechunk)
-        .with_encoding(encoding)
-        .with_columns(column_names)
-        .with_dtypes(schema.as_ref())
-        .with_null_values(Some(NullValues::AllColumns(null_char)))
-        .finish()?;
-
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ndjson(
+    binary: Binary,
+    infer_schema_length: Option<usize>,
+    dtypes: Option<Vec<(&str, &str)>>,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let bytes = decompress(binary.as_slice())?;
+    let df = read_ndjson(
+        Cursor::new(bytes.as_ref()),
+        infer_schema_length,
+        dtypes,
+        columns,
+    )?;
     Ok(ExDataFrame::new(df))
 }
 
-fn dtype_from_str(dtype: &str) -> Result<DataType, ExplorerError> {
-    match dtype {
-        "str" => Ok(DataType::Utf8),
-        "f64" => Ok(DataType::Float64),
-        "i64" => Ok(DataType::Int64),
-        "bool" => Ok(DataType::Boolean),
-        "date32(days)" => Ok(DataType::Date32),
-        "date64(ms)" => Ok(DataType::Date64),
-        _ => Err(ExplorerError::Internal("Unrecognised datatype".into())),
-    }
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_json(
+    filename: &str,
+    infer_schema_length: Option<usize>,
+    dtypes: Option<Vec<(&str, &str)>>,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let mut f = File::open(filename)?;
+    let compression = Compression::detect_file(&mut f)?;
+    let value: serde_json::Value =
+        serde_json::from_reader(BufReader::new(decoder(f, compression)?))?;
+    let df = read_json_value(value, infer_schema_length, dtypes, columns)?;
+    Ok(ExDataFrame::new(df))
 }
 
-#[rustler::nif]
-pub fn df_read_parquet(filename: &str) -> Result<ExDataFrame, ExplorerError> {
-    let f = File::open(filename)?;
-    let df = ParquetReader::new(f).finish()?;
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_json(
+    binary: Binary,
+    infer_schema_length: Option<usize>,
+    dtypes: Option<Vec<(&str, &str)>>,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let value: serde_json::Value = serde_json::from_slice(&decompress(binary.as_slice())?)?;
+    let df = read_json_value(value, infer_schema_length, dtypes, columns)?;
     Ok(ExDataFrame::new(df))
 }
 
-#[rustler::nif]
-pub fn df_to_csv(
-    data: ExDataFrame,
-    has_headers: bool,
-    delimiter: u8,
-) -> Result<String, ExplorerError> {
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_to_ndjson(data: ExDataFrame) -> Result<String, ExplorerError> {
     df_read!(data, df, {
         let mut buf: Vec<u8> = Vec::with_capacity(81920);
-        CsvWriter::new(&mut buf)
-            .has_headers(has_headers)
-            .with_delimiter(delimiter)
-            .finish(&df)?;
+        write_json(&mut buf, &df, "ndjson")?;
+        Ok(String::from_utf8(buf)?)
+    })
+}
 
-        let s = String::from_utf8(buf)?;
-        Ok(s)
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_ndjson_file(data: ExDataFrame, filename: &str) -> Result<(), ExplorerError> {
+    df_read!(data, df, {
+        let f = File::create(filename)?;
+        write_json(BufWriter::new(f), &df, "ndjson")?;
+        Ok(())
     })
 }
 
-#[rustler::nif]
-pub fn df_to_csv_file(
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_to_json(data: ExDataFrame, orient: &str) -> Result<String, ExplorerError> {
+    df_read!(data, df, {
+        let mut buf: Vec<u8> = Vec::with_capacity(81920);
+        write_json(&mut buf, &df, orient)?;
+        Ok(String::from_utf8(buf)?)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_json_file(
     data: ExDataFrame,
     filename: &str,
-    has_headers: bool,
-    delimiter: u8,
+    orient: &str,
 ) -> Result<(), ExplorerError> {
     df_read!(data, df, {
-        let mut f = File::create(filename)?;
-        CsvWriter::new(&mut f)
-            .has_headers(has_headers)
-            .with_delimiter(delimiter)
-            .finish(&df)?;
+        let f = File::create(filename)?;
+        write_json(BufWriter::new(f), &df, orient)?;
         Ok(())
     })
 }
 
+fn read_ndjson<R: Read + Seek>(
+    reader: R,
+    infer_schema_length: Option<usize>,
+    dtypes: Option<Vec<(&str, &str)>>,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, ExplorerError> {
+    let reader = JsonReader::new(reader)
+        .infer_schema(infer_schema_length)
+        .with_projection(columns);
+
+    // Explicit dtypes replace inference entirely, so only the listed fields are read.
+    let df = match dtypes {
+        Some(dtypes) => {
+            let fields = dtypes
+                .iter()
+                .map(|(name, dtype)| Ok(Field::new(name, dtype_from_str(dtype)?)))
+                .collect::<Result<Vec<Field>, ExplorerError>>()?;
+            let schema = Arc::new(Schema::new(fields).to_arrow());
+            reader.with_schema(&schema).finish()?
+        }
+        None => reader.finish()?,
+    };
+    Ok(df)
+}
+
+// Accepts either an array of records or an object of equally long column
+// arrays, and re-encodes it as NDJSON for the Arrow JSON reader.
+fn read_json_value(
+    value: serde_json::Value,
+    infer_schema_length: Option<usize>,
+    dtypes: Option<Vec<(&str, &str)>>,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, ExplorerError> {
+    let rows = match value {
+        serde_json::Value::Array(rows) => rows,
+        serde_json::Value::Object(cols) => {
+            let height = cols
+                .values()
+                .map(|c| c.as_array().map_or(0, |c| c.len()))
+                .max();
+            let mut rows = vec![serde_json::Map::new(); height.unwrap_or(0)];
+            for (name, col) in cols {
+                match col {
+                    serde_json::Value::Array(values) => {
+                        for (row, value) in rows.iter_mut().zip(values) {
+                            row.insert(name.clone(), value);
+                        }
+                    }
+                    _ => {
+                        return Err(ExplorerError::Other(format!(
+                            "Expected column {} to be a JSON array",
+                            name
+                        )))
+                    }
+                }
+            }
+            rows.into_iter().map(serde_json::Value::Object).collect()
+        }
+        _ => {
+            return Err(ExplorerError::Other(
+                "Expected a JSON array of records or an object of columns".into(),
+            ))
+        }
+    };
+
+    let mut buf: Vec<u8> = Vec::new();
+    for row in rows {
+        serde_json::to_writer(&mut buf, &row)?;
+        buf.push(b'\n');
+    }
+    read_ndjson(Cursor::new(buf), infer_schema_length, dtypes, columns)
+}
+
+fn write_json<W: Write>(mut writer: W, df: &DataFrame, orient: &str) -> Result<(), ExplorerError> {
+    let rows = record_batches_to_json_rows(&df.as_record_batches()?);
+    match orient {
+        "ndjson" => {
+            for row in rows {
+                serde_json::to_writer(&mut writer, &row)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        "records" => serde_json::to_writer(&mut writer, &rows)?,
+        "columns" => {
+            let mut cols = serde_json::Map::new();
+            for name in df.get_column_names() {
+                let values = rows
+                    .iter()
+                    .map(|row| row.get(name).cloned().unwrap_or(serde_json::Value::Null))
+                    .collect();
+                cols.insert(name.to_string(), serde_json::Value::Array(values));
+            }
+            serde_json::to_writer(&mut writer, &cols)?
+        }
+        o => {
+            return Err(ExplorerError::Other(format!(
+                "JSON orientation {} not supported",
+                o
+            )))
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 #[rustler::nif]
 pub fn df_as_str(data: ExDataFrame) -> Result<String, ExplorerError> {
     df_read!(data, df, { Ok(format!("{:?}", df)) })
@@ -152,7 +2318,7 @@ pub fn df_fill_none(data: ExDataFrame, strategy: &str) -> Result<ExDataFrame, Ex
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_join(
     data: ExDataFrame,
     other: ExDataFrame,
@@ -160,14 +2326,50 @@ pub fn df_join(
     right_on: Vec<&str>,
     how: &str,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let how = match how {
-        "left" => JoinType::Left,
-        "inner" => JoinType::Inner,
-        "outer" => JoinType::Outer,
-        "cross" => JoinType::Cross,
-        _ => return Err(ExplorerError::Other(format!("Join method {} not supported", how)).into()),
-    };
+    let how = join_type_from_str(how)?;
+    join(&data, &other, left_on, right_on, how)
+}
+
+#[rustler::nif]
+pub fn df_join_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    data: ExDataFrame,
+    other: ExDataFrame,
+    left_on: Vec<String>,
+    right_on: Vec<String>,
+    how: &str,
+) -> Result<ExOperation, ExplorerError> {
+    let how = join_type_from_str(how)?;
+    // polars cannot be interrupted halfway through a join, so a join that is
+    // cancelled while running is only dropped once it is done.
+    Ok(task::spawn(env, reference, move |_operation| {
+        let left_on = left_on.iter().map(String::as_str).collect();
+        let right_on = right_on.iter().map(String::as_str).collect();
+        join(&data, &other, left_on, right_on, how)
+    }))
+}
+
+fn join_type_from_str(how: &str) -> Result<JoinType, ExplorerError> {
+    match how {
+        "left" => Ok(JoinType::Left),
+        "inner" => Ok(JoinType::Inner),
+        "outer" => Ok(JoinType::Outer),
+        "cross" => Ok(JoinType::Cross),
+        _ => Err(ExplorerError::Other(format!(
+            "Join method {} not supported",
+            how
+        ))),
+    }
+}
 
+fn join(
+    data: &ExDataFrame,
+    other: &ExDataFrame,
+    left_on: Vec<&str>,
+    right_on: Vec<&str>,
+    how: JoinType,
+) -> Result<ExDataFrame, ExplorerError> {
     df_read_read!(data, other, df, df1, {
         let new_df = df.join(&*df1, left_on, right_on, how)?;
         Ok(ExDataFrame::new(new_df))
@@ -226,7 +2428,7 @@ pub fn df_hstack(data: ExDataFrame, cols: Vec<ExSeries>) -> Result<ExDataFrame,
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_vstack(data: ExDataFrame, other: ExDataFrame) -> Result<ExDataFrame, ExplorerError> {
     df_read_read!(data, other, df, df1, {
         Ok(ExDataFrame::new(df.vstack(&df1.clone())?))
@@ -297,6 +2499,7 @@ pub fn df_filter(data: ExDataFrame, mask: ExSeries) -> Result<ExDataFrame, Explo
 #[rustler::nif]
 pub fn df_take(data: ExDataFrame, indices: Vec<u32>) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, {
+        check_indices(&indices, df.height())?;
         let idx = UInt32Chunked::new_from_slice("idx", indices.as_slice());
         let new_df = df.take(&idx)?;
         Ok(ExDataFrame::new(new_df))
@@ -310,12 +2513,15 @@ pub fn df_take_with_series(
 ) -> Result<ExDataFrame, ExplorerError> {
     let idx = indices.resource.0.u32()?;
     df_read!(data, df, {
+        if let Some(max) = idx.max() {
+            check_bounds(max as usize, df.height())?;
+        }
         let new_df = df.take(&idx)?;
         Ok(ExDataFrame::new(new_df))
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_sort(
     data: ExDataFrame,
     by_column: &str,
@@ -327,6 +2533,22 @@ pub fn df_sort(
     })
 }
 
+#[rustler::nif]
+pub fn df_sort_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    data: ExDataFrame,
+    by_column: String,
+    reverse: bool,
+) -> Result<ExOperation, ExplorerError> {
+    Ok(task::spawn(env, reference, move |_operation| {
+        df_read!(data, df, {
+            let new_df = df.sort(&by_column, reverse)?;
+            Ok(ExDataFrame::new(new_df))
+        })
+    }))
+}
+
 #[rustler::nif]
 pub fn df_replace(
     data: ExDataFrame,
@@ -368,7 +2590,7 @@ pub fn df_tail(data: ExDataFrame, length: Option<usize>) -> Result<ExDataFrame,
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_is_unique(data: ExDataFrame) -> Result<ExSeries, ExplorerError> {
     df_read!(data, df, {
         let mask = df.is_unique()?;
@@ -376,7 +2598,7 @@ pub fn df_is_unique(data: ExDataFrame) -> Result<ExSeries, ExplorerError> {
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_is_duplicated(data: ExDataFrame) -> Result<ExSeries, ExplorerError> {
     df_read!(data, df, {
         let mask = df.is_unique()?;
@@ -405,7 +2627,7 @@ pub fn df_clone(data: ExDataFrame) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, { Ok(ExDataFrame::new(df.clone())) })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_explode(data: ExDataFrame, cols: Vec<String>) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, {
         let new_df = df.explode(&cols)?;
@@ -413,7 +2635,7 @@ pub fn df_explode(data: ExDataFrame, cols: Vec<String>) -> Result<ExDataFrame, E
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_melt(
     data: ExDataFrame,
     id_vars: Vec<&str>,
@@ -433,7 +2655,7 @@ pub fn df_shift(data: ExDataFrame, periods: i64) -> Result<ExDataFrame, Explorer
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_drop_duplicates(
     data: ExDataFrame,
     maintain_order: bool,
@@ -475,12 +2697,12 @@ pub fn df_var(data: ExDataFrame) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, { Ok(ExDataFrame::new(df.var())) })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_median(data: ExDataFrame) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, { Ok(ExDataFrame::new(df.median())) })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_quantile(data: ExDataFrame, quant: f64) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, {
         let new_df = df.quantile(quant)?;
@@ -488,7 +2710,7 @@ pub fn df_quantile(data: ExDataFrame, quant: f64) -> Result<ExDataFrame, Explore
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_to_dummies(data: ExDataFrame) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, {
         let new_df = df.to_dummies()?;
@@ -530,16 +2752,17 @@ pub fn df_cast(
     column: &str,
     to_type: &str,
 ) -> Result<ExDataFrame, ExplorerError> {
+    let dtype = cast_dtype_from_str(to_type)?;
     df_read!(data, df, {
         let new_df = df
             .clone()
-            .may_apply(column, |s: &Series| cast(s, to_type))?
+            .may_apply(column, |s: &Series| s.cast_with_dtype(&dtype))?
             .clone();
         Ok(ExDataFrame::new(new_df))
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_groups(data: ExDataFrame, groups: Vec<&str>) -> Result<ExDataFrame, ExplorerError> {
     df_read!(data, df, {
         let groups = df.groupby(groups)?.groups()?;
@@ -547,7 +2770,7 @@ pub fn df_groups(data: ExDataFrame, groups: Vec<&str>) -> Result<ExDataFrame, Ex
     })
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_groupby_agg(
     data: ExDataFrame,
     groups: Vec<&str>,
@@ -560,6 +2783,46 @@ pub fn df_groupby_agg(
 }
 
 #[rustler::nif]
+pub fn df_groupby_agg_async<'a>(
+    env: Env<'a>,
+    reference: Term<'a>,
+    data: ExDataFrame,
+    groups: Vec<String>,
+    aggs: Vec<(String, Vec<String>)>,
+) -> Result<ExOperation, ExplorerError> {
+    Ok(task::spawn(env, reference, move |operation| {
+        let n_groups = groups.len();
+        let groups: Vec<&str> = groups.iter().map(String::as_str).collect();
+        df_read!(data, df, {
+            let groupby = df.groupby(groups)?;
+            operation.check_cancelled()?;
+            // Aggregating one column at a time lets a cancelled operation
+            // stop in between. The groups are shared, so the rows line up.
+            let mut new_df: Option<DataFrame> = None;
+            for (column, funs) in &aggs {
+                let funs: Vec<&str> = funs.iter().map(String::as_str).collect();
+                let agg = groupby.agg(&[(column.as_str(), funs)])?;
+                match new_df.as_mut() {
+                    Some(new_df) => {
+                        new_df.hstack_mut(&agg.get_columns()[n_groups..])?;
+                    }
+                    None => new_df = Some(agg),
+                }
+                operation.check_cancelled()?;
+            }
+            let new_df = match new_df {
+                Some(new_df) => new_df,
+                None => {
+                    let no_aggs: &[(&str, Vec<&str>)] = &[];
+                    groupby.agg(no_aggs)?
+                }
+            };
+            Ok(ExDataFrame::new(new_df))
+        })
+    }))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_pivot_wider(
     data: ExDataFrame,
     id_cols: Vec<&str>,
@@ -1,7 +1,12 @@
 use polars::prelude::*;
 use rustler::resource::ResourceArc;
-use rustler::{Encoder, Env, NifStruct, Term};
-use std::sync::RwLock;
+use rustler::{Encoder, Env, NifMap, NifStruct, NifUntaggedEnum, Term};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::dataframe::{CsvBatchReader, CsvBatchWriter};
+use crate::series::not_implemented;
+use crate::ExplorerError;
 
 use std::result::Result;
 
@@ -20,6 +25,105 @@ pub struct ExSeries {
     pub resource: ResourceArc<ExSeriesRef>,
 }
 
+pub struct ExCsvReaderRef(pub Mutex<CsvBatchReader>);
+
+#[derive(NifStruct)]
+#[module = "Explorer.PolarsBackend.CsvReader"]
+pub struct ExCsvReader {
+    pub resource: ResourceArc<ExCsvReaderRef>,
+}
+
+pub struct ExCsvWriterRef(pub Mutex<Option<CsvBatchWriter>>);
+
+#[derive(NifStruct)]
+#[module = "Explorer.PolarsBackend.CsvWriter"]
+pub struct ExCsvWriter {
+    pub resource: ResourceArc<ExCsvWriterRef>,
+}
+
+/// The cancellation flag of an operation started with one of the `_async`
+/// NIFs. It is only raised by `df_cancel_async`, so a caller may drop the
+/// handle and still receive the result.
+pub struct ExOperationRef(pub Arc<AtomicBool>);
+
+#[derive(NifStruct)]
+#[module = "Explorer.PolarsBackend.Operation"]
+pub struct ExOperation {
+    pub resource: ResourceArc<ExOperationRef>,
+}
+
+#[derive(NifUntaggedEnum, Debug)]
+pub enum ExScalar {
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+#[derive(NifMap)]
+pub struct ExParquetMetadata {
+    pub schema: Vec<(String, String)>,
+    pub num_rows: i64,
+    pub num_row_groups: usize,
+    pub statistics: Vec<ExColumnStatistics>,
+}
+
+#[derive(NifMap)]
+pub struct ExColumnStatistics {
+    pub name: String,
+    pub min: Option<ExScalar>,
+    pub max: Option<ExScalar>,
+    pub null_count: Option<u64>,
+}
+
+#[derive(NifMap)]
+pub struct ExCsvReadOptions {
+    pub infer_schema_length: Option<usize>,
+    pub has_header: bool,
+    pub stop_after_n_rows: Option<usize>,
+    pub skip_rows: usize,
+    pub projection: Option<Vec<usize>>,
+    pub delimiter: String,
+    pub rechunk: bool,
+    pub columns: Option<Vec<String>>,
+    pub dtypes: Option<Vec<(String, String)>>,
+    pub encoding: String,
+    pub null_char: String,
+    pub quote_char: Option<String>,
+    pub comment_char: Option<String>,
+    pub column_null_values: Option<Vec<(String, String)>>,
+    pub skip_footer: usize,
+}
+
+#[derive(NifMap)]
+pub struct ExCsvWriteOptions {
+    pub has_headers: bool,
+    pub delimiter: u8,
+    pub quote_style: String,
+    pub null_value: String,
+    pub date_format: Option<String>,
+    pub datetime_format: Option<String>,
+    pub float_precision: Option<usize>,
+}
+
+impl PartialEq for ExScalar {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for ExScalar {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (ExScalar::Int(a), ExScalar::Int(b)) => a.partial_cmp(b),
+            (ExScalar::Int(a), ExScalar::Float(b)) => (*a as f64).partial_cmp(b),
+            (ExScalar::Float(a), ExScalar::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (ExScalar::Float(a), ExScalar::Float(b)) => a.partial_cmp(b),
+            (ExScalar::Str(a), ExScalar::Str(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
 impl ExDataFrameRef {
     pub fn new(df: DataFrame) -> Self {
         Self(RwLock::new(df))
@@ -32,6 +136,30 @@ impl ExSeriesRef {
     }
 }
 
+impl ExCsvReader {
+    pub fn new(reader: CsvBatchReader) -> Self {
+        Self {
+            resource: ResourceArc::new(ExCsvReaderRef(Mutex::new(reader))),
+        }
+    }
+}
+
+impl ExCsvWriter {
+    pub fn new(writer: CsvBatchWriter) -> Self {
+        Self {
+            resource: ResourceArc::new(ExCsvWriterRef(Mutex::new(Some(writer)))),
+        }
+    }
+}
+
+impl ExOperation {
+    pub fn new() -> Self {
+        Self {
+            resource: ResourceArc::new(ExOperationRef(Arc::new(AtomicBool::new(false)))),
+        }
+    }
+}
+
 impl ExDataFrame {
     pub fn new(df: DataFrame) -> Self {
         Self {
@@ -50,59 +178,61 @@ impl ExSeries {
 
 macro_rules! encode {
     ($s:ident, $env:ident, $convert_function:ident, $out_type:ty) => {
-        $s.$convert_function()
-            .unwrap()
+        $s.$convert_function()?
             .into_iter()
-            .map(|item| item)
             .collect::<Vec<Option<$out_type>>>()
             .encode($env)
     };
     ($s:ident, $env:ident, $convert_function:ident) => {
-        $s.$convert_function()
-            .unwrap()
+        $s.$convert_function()?
             .into_iter()
-            .map(|item| item)
             .collect::<Vec<Option<$convert_function>>>()
             .encode($env)
     };
 }
 
-macro_rules! encode_list {
-    ($s:ident, $env:ident, $convert_function:ident, $out_type:ty) => {
-        $s.list()
-            .unwrap()
-            .into_iter()
-            .map(|item| item)
-            .collect::<Vec<Option<Series>>>()
-            .iter()
-            .map(|item| {
-                item.clone()
-                    .unwrap()
-                    .$convert_function()
-                    .unwrap()
-                    .into_iter()
-                    .map(|item| item)
-                    .collect::<Vec<Option<$out_type>>>()
-            })
-            .collect::<Vec<Vec<Option<$out_type>>>>()
-            .encode($env)
-    };
+impl ExSeriesRef {
+    pub fn to_list<'b>(&self, env: Env<'b>) -> Result<Term<'b>, ExplorerError> {
+        encode_series(&self.0, env)
+    }
 }
 
-impl<'a> Encoder for ExSeriesRef {
-    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
-        let s = &self.0;
-        match s.dtype() {
-            DataType::Boolean => encode!(s, env, bool),
-            DataType::Utf8 => encode!(s, env, utf8, &str),
-            DataType::Int32 => encode!(s, env, i32),
-            DataType::Int64 => encode!(s, env, i64),
-            DataType::UInt32 => encode!(s, env, u32),
-            DataType::Float64 => encode!(s, env, f64),
-            DataType::Date32 => encode!(s, env, date32, i32),
-            DataType::Date64 => encode!(s, env, date64, i64),
-            DataType::List(ArrowDataType::UInt32) => encode_list!(s, env, u32, u32),
-            dt => panic!("to_list/1 not implemented for {:?}", dt),
+/// Encodes a series as an Elixir list. List series are encoded as a list of
+/// lists, with `nil` for null rows, by encoding each inner series in turn.
+pub(crate) fn encode_series<'b>(s: &Series, env: Env<'b>) -> Result<Term<'b>, ExplorerError> {
+    let term = match s.dtype() {
+        DataType::Boolean => encode!(s, env, bool),
+        DataType::Utf8 => encode!(s, env, utf8, &str),
+        DataType::Int8 => encode!(s, env, i8),
+        DataType::Int16 => encode!(s, env, i16),
+        DataType::Int32 => encode!(s, env, i32),
+        DataType::Int64 => encode!(s, env, i64),
+        DataType::UInt8 => encode!(s, env, u8),
+        DataType::UInt16 => encode!(s, env, u16),
+        DataType::UInt32 => encode!(s, env, u32),
+        DataType::UInt64 => encode!(s, env, u64),
+        DataType::Float32 => encode!(s, env, f32),
+        DataType::Float64 => encode!(s, env, f64),
+        DataType::Date32 => encode!(s, env, date32, i32),
+        DataType::Date64 => encode!(s, env, date64, i64),
+        DataType::Categorical => {
+            let s = s.cast::<Utf8Type>()?;
+            encode!(s, env, utf8, &str)
         }
-    }
+        DataType::Duration(_) | DataType::Time64(_) => {
+            let s = s.cast::<Int64Type>()?;
+            encode!(s, env, i64)
+        }
+        DataType::List(_) => s
+            .list()?
+            .into_iter()
+            .map(|inner| match inner {
+                Some(inner) => encode_series(&inner, env),
+                None => Ok(None::<bool>.encode(env)),
+            })
+            .collect::<Result<Vec<Term>, ExplorerError>>()?
+            .encode(env),
+        dt => return Err(not_implemented("to_list/1", dt)),
+    };
+    Ok(term)
 }
@@ -1,10 +1,29 @@
+use polars::prelude::PolarsError;
 use rustler::{Encoder, Env, Term};
 use std::io;
 use thiserror::Error;
 
 rustler::atoms! {
-    ok,
-    error
+    arrow,
+    cancelled,
+    column_not_found,
+    data_type_mismatch,
+    internal,
+    invalid_dtype,
+    io,
+    json,
+    other,
+    out_of_bounds,
+    parquet,
+    polars,
+    schema_mismatch,
+    shape_mismatch,
+    unknown,
+    utf8,
+    // io reasons, named after their POSIX errors like in :file
+    eacces,
+    eexist,
+    enoent,
 }
 
 #[derive(Error, Debug)]
@@ -14,7 +33,23 @@ pub enum ExplorerError {
     #[error("Utf8 Conversion Error")]
     Utf8(#[from] std::string::FromUtf8Error),
     #[error("Polars Error")]
-    Polars(#[from] polars::prelude::PolarsError),
+    Polars(#[from] PolarsError),
+    #[error("Parquet Error")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("Arrow Error")]
+    Arrow(#[from] polars::export::arrow::error::ArrowError),
+    #[error("JSON Error")]
+    Json(#[from] serde_json::Error),
+    #[error("Column not found: {0}")]
+    ColumnNotFound(String),
+    #[error("Schema mismatch: {0}")]
+    SchemaMismatch(String),
+    #[error("Invalid dtype: {0}")]
+    InvalidDtype(String),
+    #[error("Out of bounds: {0}")]
+    OutOfBounds(String),
+    #[error("Operation cancelled")]
+    Cancelled,
     #[error("Internal Error: {0}")]
     Internal(String),
     #[error("Other error: {0}")]
@@ -23,8 +58,50 @@ pub enum ExplorerError {
     Unknown(#[from] anyhow::Error),
 }
 
+/// Encodes errors as `{kind, details}` tuples, so that they can be matched
+/// on from Elixir, e.g. `{:column_not_found, "foo"}` or `{:io, :enoent}`.
 impl<'a> Encoder for ExplorerError {
     fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
-        format!("{:?}", self).encode(env)
+        match self {
+            ExplorerError::Io(e) => encode_io_error(env, e),
+            ExplorerError::Utf8(e) => (utf8(), e.to_string()).encode(env),
+            ExplorerError::Polars(e) => encode_polars_error(env, e),
+            ExplorerError::Parquet(e) => (parquet(), e.to_string()).encode(env),
+            ExplorerError::Arrow(e) => (arrow(), e.to_string()).encode(env),
+            ExplorerError::Json(e) => (json(), e.to_string()).encode(env),
+            ExplorerError::ColumnNotFound(name) => (column_not_found(), name).encode(env),
+            ExplorerError::SchemaMismatch(details) => (schema_mismatch(), details).encode(env),
+            ExplorerError::InvalidDtype(dtype) => (invalid_dtype(), dtype).encode(env),
+            ExplorerError::OutOfBounds(details) => (out_of_bounds(), details).encode(env),
+            ExplorerError::Cancelled => (cancelled(), "operation cancelled").encode(env),
+            ExplorerError::Internal(details) => (internal(), details).encode(env),
+            ExplorerError::Other(details) => (other(), details).encode(env),
+            ExplorerError::Unknown(e) => (unknown(), e.to_string()).encode(env),
+        }
+    }
+}
+
+fn encode_io_error<'b>(env: Env<'b>, e: &io::Error) -> Term<'b> {
+    match e.kind() {
+        io::ErrorKind::NotFound => (io(), enoent()).encode(env),
+        io::ErrorKind::PermissionDenied => (io(), eacces()).encode(env),
+        io::ErrorKind::AlreadyExists => (io(), eexist()).encode(env),
+        _ => (io(), e.to_string()).encode(env),
+    }
+}
+
+fn encode_polars_error<'b>(env: Env<'b>, e: &PolarsError) -> Term<'b> {
+    match e {
+        PolarsError::NotFound(name) => (column_not_found(), name.to_string()).encode(env),
+        PolarsError::SchemaMisMatch(details) => {
+            (schema_mismatch(), details.to_string()).encode(env)
+        }
+        PolarsError::ShapeMisMatch(details) => (shape_mismatch(), details.to_string()).encode(env),
+        PolarsError::DataTypeMisMatch(details) => {
+            (data_type_mismatch(), details.to_string()).encode(env)
+        }
+        PolarsError::OutOfBounds(details) => (out_of_bounds(), details.to_string()).encode(env),
+        PolarsError::Io(e) => encode_io_error(env, e),
+        e => (polars(), e.to_string()).encode(env),
     }
 }
@@ -4,19 +4,31 @@ use rustler::{Env, Term};
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+mod compression;
 mod dataframe;
 mod datatypes;
 mod error;
 mod series;
+mod task;
 
 use dataframe::*;
-pub use datatypes::{ExDataFrame, ExDataFrameRef, ExSeries, ExSeriesRef};
+pub use datatypes::{
+    ExColumnStatistics, ExCsvReadOptions, ExCsvReader, ExCsvReaderRef, ExCsvWriteOptions,
+    ExCsvWriter, ExCsvWriterRef, ExDataFrame, ExDataFrameRef, ExOperation, ExOperationRef,
+    ExParquetMetadata, ExScalar, ExSeries, ExSeriesRef,
+};
 pub use error::ExplorerError;
 use series::*;
 
 fn on_load(env: Env, _info: Term) -> bool {
     rustler::resource!(ExDataFrameRef, env);
     rustler::resource!(ExSeriesRef, env);
+    rustler::resource!(ExCsvReaderRef, env);
+    rustler::resource!(ExCsvWriterRef, env);
+    rustler::resource!(ExOperationRef, env);
+    // Categorical codes are only comparable between series built under the
+    // same string cache, which joins and stacking on categoricals rely on.
+    polars::toggle_string_cache(true);
     true
 }
 
@@ -24,14 +36,20 @@ rustler::init!(
     "Elixir.Explorer.PolarsBackend.Native",
     [
         df_as_str,
+        df_cancel_async,
         df_cast,
         df_clone,
+        df_close_csv_writer,
         df_column,
         df_columns,
+        df_csv_schema,
         df_drop,
         df_drop_duplicates,
         df_drop_nulls,
         df_dtypes,
+        df_dump_ipc,
+        df_dump_ipc_stream,
+        df_dump_parquet,
         df_explode,
         df_fill_none,
         df_filter,
@@ -40,12 +58,20 @@ rustler::init!(
         df_get_columns,
         df_groups,
         df_groupby_agg,
+        df_groupby_agg_async,
         df_head,
         df_height,
         df_hstack,
         df_is_duplicated,
         df_is_unique,
         df_join,
+        df_join_async,
+        df_load_csv,
+        df_load_ipc,
+        df_load_ipc_stream,
+        df_load_json,
+        df_load_ndjson,
+        df_load_parquet,
         df_max,
         df_mean,
         df_median,
@@ -53,10 +79,22 @@ rustler::init!(
         df_min,
         df_n_chunks,
         df_new,
+        df_next_csv_batch,
+        df_open_csv_writer,
+        df_parquet_metadata,
         df_pivot_wider,
         df_quantile,
         df_read_csv,
+        df_read_csv_async,
+        df_read_csv_batched,
+        df_read_dataset,
+        df_read_ipc,
+        df_read_ipc_stream,
+        df_read_json,
+        df_read_ndjson,
         df_read_parquet,
+        df_read_parquet_async,
+        df_read_partitioned,
         df_replace,
         df_select,
         df_select_at_idx,
@@ -65,6 +103,7 @@ rustler::init!(
         df_shift,
         df_slice,
         df_sort,
+        df_sort_async,
         df_stdev,
         df_sum,
         df_tail,
@@ -72,11 +111,22 @@ rustler::init!(
         df_take_with_series,
         df_to_csv,
         df_to_csv_file,
+        df_to_csv_file_async,
         df_to_dummies,
+        df_to_json,
+        df_to_json_file,
+        df_to_ndjson,
+        df_to_ndjson_file,
         df_var,
         df_vstack,
         df_width,
         df_with_column,
+        df_write_csv_batch,
+        df_write_ipc,
+        df_write_ipc_stream,
+        df_write_parquet,
+        df_write_parquet_async,
+        df_write_partitioned,
         // series
         s_add,
         s_append,
@@ -84,6 +134,7 @@ rustler::init!(
         s_argsort,
         s_as_str,
         s_cast,
+        s_categories,
         s_clone,
         s_cum_max,
         s_cum_min,
@@ -105,6 +156,16 @@ rustler::init!(
         s_is_unique,
         s_len,
         s_limit,
+        s_list_contains,
+        s_list_get,
+        s_list_join,
+        s_list_lengths,
+        s_list_max,
+        s_list_mean,
+        s_list_min,
+        s_list_sort,
+        s_list_sum,
+        s_list_unique,
         s_lt,
         s_lt_eq,
         s_max,
@@ -119,9 +180,21 @@ rustler::init!(
         s_new_bool,
         s_new_date32,
         s_new_date64,
+        s_new_duration_ms,
+        s_new_duration_ns,
+        s_new_f32,
         s_new_f64,
+        s_new_i16,
+        s_new_i32,
         s_new_i64,
+        s_new_i8,
+        s_new_list,
         s_new_str,
+        s_new_time64,
+        s_new_u16,
+        s_new_u32,
+        s_new_u64,
+        s_new_u8,
         s_not,
         s_null_count,
         s_peak_max,
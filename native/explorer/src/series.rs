@@ -5,7 +5,9 @@ use rand_pcg::Pcg64;
 use rustler::{Encoder, Env, Term};
 use std::result::Result;
 
-use crate::{ExDataFrame, ExSeries, ExSeriesRef, ExplorerError};
+use crate::dataframe::compare_scalar;
+use crate::datatypes::encode_series;
+use crate::{ExDataFrame, ExScalar, ExSeries, ExplorerError};
 
 pub(crate) fn to_series_collection(s: Vec<ExSeries>) -> Vec<Series> {
     s.into_iter().map(|c| c.resource.0.clone()).collect()
@@ -23,28 +25,74 @@ pub fn s_as_str(data: ExSeries) -> Result<String, ExplorerError> {
 macro_rules! init_method {
     ($name:ident, $type:ty) => {
         #[rustler::nif]
-        pub fn $name(name: &str, val: Vec<Option<$type>>) -> ExSeries {
-            ExSeries::new(Series::new(name, val.as_slice()))
+        pub fn $name(name: &str, val: Vec<Option<$type>>) -> Result<ExSeries, ExplorerError> {
+            Ok(ExSeries::new(Series::new(name, val.as_slice())))
         }
     };
     ($name:ident, $type:ty, $cast_type:ty) => {
         #[rustler::nif]
-        pub fn $name(name: &str, val: Vec<Option<$type>>) -> ExSeries {
-            ExSeries::new(
-                Series::new(name, val.as_slice())
-                    .cast::<$cast_type>()
-                    .unwrap(),
-            )
+        pub fn $name(name: &str, val: Vec<Option<$type>>) -> Result<ExSeries, ExplorerError> {
+            Ok(ExSeries::new(
+                Series::new(name, val.as_slice()).cast::<$cast_type>()?,
+            ))
         }
     };
 }
 
+macro_rules! init_temporal_method {
+    ($name:ident, $type:ty) => {
+        #[rustler::nif]
+        pub fn $name(name: &str, val: Vec<Option<i64>>) -> Result<ExSeries, ExplorerError> {
+            Ok(ExSeries::new(
+                ChunkedArray::<$type>::new_from_opt_slice(name, val.as_slice()).into_series(),
+            ))
+        }
+    };
+}
+
+init_method!(s_new_i8, i8);
+init_method!(s_new_i16, i16);
+init_method!(s_new_i32, i32);
 init_method!(s_new_i64, i64);
+init_method!(s_new_u8, u8);
+init_method!(s_new_u16, u16);
+init_method!(s_new_u32, u32);
+init_method!(s_new_u64, u64);
 init_method!(s_new_bool, bool);
 init_method!(s_new_date32, &str, Date32Type);
 init_method!(s_new_date64, &str, Date64Type);
+init_method!(s_new_f32, f32);
 init_method!(s_new_f64, f64);
 init_method!(s_new_str, String);
+init_temporal_method!(s_new_duration_ms, DurationMillisecondType);
+init_temporal_method!(s_new_duration_ns, DurationNanosecondType);
+init_temporal_method!(s_new_time64, Time64NanosecondType);
+
+#[rustler::nif]
+pub fn s_new_list(name: &str, val: Vec<Option<ExSeries>>) -> Result<ExSeries, ExplorerError> {
+    let rows: Vec<Option<Series>> = val
+        .into_iter()
+        .map(|row| row.map(|s| s.resource.0.clone()))
+        .collect();
+    let dtype = match rows.iter().flatten().next() {
+        Some(row) => row.dtype().clone(),
+        None => {
+            return Err(ExplorerError::Other(
+                "cannot make a list series without any inner series".into(),
+            ))
+        }
+    };
+    if let Some(row) = rows.iter().flatten().find(|row| row.dtype() != &dtype) {
+        return Err(ExplorerError::Other(format!(
+            "cannot make a list series from mismatched inner dtypes {:?} and {:?}",
+            dtype,
+            row.dtype()
+        )));
+    }
+    let mut ca: ListChunked = rows.into_iter().collect();
+    ca.rename(name);
+    Ok(ExSeries::new(ca.into_series()))
+}
 
 #[rustler::nif]
 pub fn s_rechunk(data: ExSeries) -> Result<ExSeries, ExplorerError> {
@@ -116,14 +164,52 @@ pub fn s_filter(data: ExSeries, filter: ExSeries) -> Result<ExSeries, ExplorerEr
 pub fn s_add(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
-    Ok(ExSeries::new(s + s1))
+    let result = match (s.dtype(), s1.dtype()) {
+        (DataType::Date32 | DataType::Date64, DataType::Duration(_))
+        | (DataType::Duration(_), DataType::Date32 | DataType::Date64) => {
+            datetime_from_millis(s.name(), &millis(s)? + &millis(s1)?)
+        }
+        _ => s + s1,
+    };
+    Ok(ExSeries::new(result))
 }
 
 #[rustler::nif]
 pub fn s_sub(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
-    Ok(ExSeries::new(s - s1))
+    let result = match (s.dtype(), s1.dtype()) {
+        (DataType::Date32 | DataType::Date64, DataType::Date32 | DataType::Date64) => {
+            duration_from_millis(s.name(), &millis(s)? - &millis(s1)?)
+        }
+        (DataType::Date32 | DataType::Date64, DataType::Duration(_)) => {
+            datetime_from_millis(s.name(), &millis(s)? - &millis(s1)?)
+        }
+        _ => s - s1,
+    };
+    Ok(ExSeries::new(result))
+}
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+// Dates, datetimes and durations are lined up as milliseconds, the unit of
+// Date64, which is the finest datetime the pinned polars has.
+fn millis(s: &Series) -> Result<Int64Chunked, ExplorerError> {
+    let ms = match s.dtype() {
+        DataType::Date32 => &s.cast::<Int64Type>()? * MS_PER_DAY,
+        DataType::Date64 | DataType::Duration(TimeUnit::Millisecond) => s.cast::<Int64Type>()?,
+        DataType::Duration(TimeUnit::Nanosecond) => &s.cast::<Int64Type>()? / 1_000_000,
+        dt => return Err(not_implemented("temporal arithmetic", dt)),
+    };
+    Ok(ms.i64()?.clone())
+}
+
+fn datetime_from_millis(name: &str, ms: Int64Chunked) -> Series {
+    ChunkedArray::<Date64Type>::new_from_opt_iter(name, ms.into_iter()).into_series()
+}
+
+fn duration_from_millis(name: &str, ms: Int64Chunked) -> Series {
+    ChunkedArray::<DurationMillisecondType>::new_from_opt_iter(name, ms.into_iter()).into_series()
 }
 
 #[rustler::nif]
@@ -152,26 +238,26 @@ pub fn s_tail(data: ExSeries, length: Option<usize>) -> Result<ExSeries, Explore
     Ok(ExSeries::new(s.tail(length)))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_sort(data: ExSeries, reverse: bool) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     Ok(ExSeries::new(s.sort(reverse)))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_argsort(data: ExSeries, reverse: bool) -> Result<Vec<Option<u32>>, ExplorerError> {
     let s = &data.resource.0;
     Ok(s.argsort(reverse).into_iter().collect::<Vec<Option<u32>>>())
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_unique(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let unique = s.unique()?;
     Ok(ExSeries::new(unique))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_value_counts(data: ExSeries) -> Result<ExDataFrame, ExplorerError> {
     let s = &data.resource.0;
     let mut df = s.value_counts()?;
@@ -184,6 +270,7 @@ pub fn s_value_counts(data: ExSeries) -> Result<ExDataFrame, ExplorerError> {
 #[rustler::nif]
 pub fn s_take(data: ExSeries, indices: Vec<u32>) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
+    check_indices(&indices, s.len())?;
     let idx = UInt32Chunked::new_from_slice("idx", indices.as_slice());
     let s1 = s.take(&idx)?;
     Ok(ExSeries::new(s1))
@@ -207,7 +294,20 @@ pub fn s_is_not_null(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(s.is_not_null().into_series()))
 }
 
-#[rustler::nif]
+// Returns the categories used by a categorical series, ordered by code.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_categories(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    match s.dtype() {
+        DataType::Categorical => {
+            let categories = s.unique()?.sort(false).cast::<Utf8Type>()?.drop_nulls();
+            Ok(ExSeries::new(categories))
+        }
+        dt => Err(not_implemented("categories/1", dt)),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_is_unique(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let ca = s.is_unique()?;
@@ -221,7 +321,7 @@ pub fn s_arg_true(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(ca.into_series()))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_is_duplicated(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let ca = s.is_duplicated()?;
@@ -410,7 +510,7 @@ pub fn s_str_to_lowercase(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(s1))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_str_parse_date32(data: ExSeries, fmt: Option<&str>) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     if let Ok(ca) = s.utf8() {
@@ -421,7 +521,7 @@ pub fn s_str_parse_date32(data: ExSeries, fmt: Option<&str>) -> Result<ExSeries,
     }
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_str_parse_date64(data: ExSeries, fmt: Option<&str>) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     if let Ok(ca) = s.utf8() {
@@ -432,14 +532,196 @@ pub fn s_str_parse_date64(data: ExSeries, fmt: Option<&str>) -> Result<ExSeries,
     }
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_lengths(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let mut ca: UInt32Chunked = s
+        .list()?
+        .into_iter()
+        .map(|row| row.map(|row| row.len() as u32))
+        .collect();
+    ca.rename(s.name());
+    Ok(ExSeries::new(ca.into_series()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_get(data: ExSeries, idx: i64) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let s1 = list_apply_scalar(s, |row| {
+        let len = row.len() as i64;
+        let idx = if idx < 0 { len + idx } else { idx };
+        if idx < 0 || idx >= len {
+            Ok(Series::full_null(row.name(), 1, row.dtype()))
+        } else {
+            Ok(row.slice(idx, 1))
+        }
+    })?;
+    Ok(ExSeries::new(s1))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_contains(data: ExSeries, value: ExScalar) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let mut ca: BooleanChunked = s
+        .list()?
+        .into_iter()
+        .map(|row| {
+            row.map(|row| {
+                let mask = compare_scalar(&row, "eq", &value)?;
+                Ok(mask.into_iter().any(|v| v == Some(true)))
+            })
+            .transpose()
+        })
+        .collect::<Result<_, ExplorerError>>()?;
+    ca.rename(s.name());
+    Ok(ExSeries::new(ca.into_series()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_join(data: ExSeries, separator: &str) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let mut ca: Utf8Chunked = s
+        .list()?
+        .into_iter()
+        .map(|row| {
+            row.map(|row| {
+                let row = row.cast::<Utf8Type>()?;
+                let values: Vec<&str> = row.utf8()?.into_iter().flatten().collect();
+                Ok(values.join(separator))
+            })
+            .transpose()
+        })
+        .collect::<Result<_, ExplorerError>>()?;
+    ca.rename(s.name());
+    Ok(ExSeries::new(ca.into_series()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_sum(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    match list_inner_dtype(s)? {
+        dt if is_numeric(&dt) => Ok(ExSeries::new(list_apply_scalar(s, |row| {
+            Ok(row.sum_as_series())
+        })?)),
+        dt => Err(not_implemented("list_sum/1", &dt)),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_mean(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    match list_inner_dtype(s)? {
+        dt if is_numeric(&dt) => Ok(ExSeries::new(list_apply_scalar(s, |row| {
+            Ok(row.mean_as_series())
+        })?)),
+        dt => Err(not_implemented("list_mean/1", &dt)),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_min(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    match list_inner_dtype(s)? {
+        DataType::Boolean | DataType::Utf8 | DataType::Categorical | DataType::List(_) => {
+            Err(not_implemented("list_min/1", s.dtype()))
+        }
+        _ => Ok(ExSeries::new(list_apply_scalar(s, |row| {
+            Ok(row.min_as_series())
+        })?)),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_max(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    match list_inner_dtype(s)? {
+        DataType::Boolean | DataType::Utf8 | DataType::Categorical | DataType::List(_) => {
+            Err(not_implemented("list_max/1", s.dtype()))
+        }
+        _ => Ok(ExSeries::new(list_apply_scalar(s, |row| {
+            Ok(row.max_as_series())
+        })?)),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_sort(data: ExSeries, reverse: bool) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let s1 = list_apply(s, |row| Ok(row.sort(reverse)))?;
+    Ok(ExSeries::new(s1))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_list_unique(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let s1 = list_apply(s, |row| Ok(row.unique()?))?;
+    Ok(ExSeries::new(s1))
+}
+
+fn list_inner_dtype(s: &Series) -> Result<DataType, ExplorerError> {
+    match s.dtype() {
+        DataType::List(dt) => Ok(DataType::from(dt)),
+        dt => Err(ExplorerError::Other(format!(
+            "expected a list series, got {:?}",
+            dt
+        ))),
+    }
+}
+
+/// Applies `f` to every row of a list series, keeping the result a list
+/// series. Null rows stay null.
+fn list_apply<F>(s: &Series, f: F) -> Result<Series, ExplorerError>
+where
+    F: Fn(Series) -> Result<Series, ExplorerError>,
+{
+    let ca = s.list()?;
+    // There's no inner series to take the dtype of the result from.
+    if ca.null_count() == ca.len() {
+        return Ok(s.clone());
+    }
+    let rows = ca
+        .into_iter()
+        .map(|row| row.map(&f).transpose())
+        .collect::<Result<Vec<Option<Series>>, ExplorerError>>()?;
+    let mut ca: ListChunked = rows.into_iter().collect();
+    ca.rename(s.name());
+    Ok(ca.into_series())
+}
+
+/// Applies `f`, which returns a series of length one, to every row of a list
+/// series and stacks the results into a flat series. Null rows stay null.
+fn list_apply_scalar<F>(s: &Series, f: F) -> Result<Series, ExplorerError>
+where
+    F: Fn(Series) -> Result<Series, ExplorerError>,
+{
+    let rows = s
+        .list()?
+        .into_iter()
+        .map(|row| row.map(&f).transpose())
+        .collect::<Result<Vec<Option<Series>>, ExplorerError>>()?;
+    let dtype = match rows.iter().flatten().next() {
+        Some(row) => row.dtype().clone(),
+        None => list_inner_dtype(s)?,
+    };
+    let mut out = Series::full_null(s.name(), 0, &dtype);
+    for row in rows {
+        match row {
+            Some(row) => out.append(&row.cast_with_dtype(&dtype)?)?,
+            None => out.append(&Series::full_null(s.name(), 1, &dtype))?,
+        };
+    }
+    // Every append above adds a chunk, so merge them into one.
+    Ok(out.rechunk())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_to_dummies(data: ExSeries) -> Result<ExDataFrame, ExplorerError> {
     let s = &data.resource.0;
     let df = s.to_dummies()?;
     Ok(ExDataFrame::new(df))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_rolling_sum(
     data: ExSeries,
     window_size: u32,
@@ -457,7 +739,7 @@ pub fn s_rolling_sum(
     Ok(ExSeries::new(s1))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_rolling_mean(
     data: ExSeries,
     window_size: u32,
@@ -475,7 +757,7 @@ pub fn s_rolling_mean(
     Ok(ExSeries::new(s1))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_rolling_max(
     data: ExSeries,
     window_size: u32,
@@ -493,7 +775,7 @@ pub fn s_rolling_max(
     Ok(ExSeries::new(s1))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_rolling_min(
     data: ExSeries,
     window_size: u32,
@@ -513,18 +795,25 @@ pub fn s_rolling_min(
 
 #[rustler::nif]
 pub fn s_to_list(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
-    let s = ExSeriesRef(data.resource.0.clone());
-    Ok(s.encode(env))
+    data.resource.to_list(env)
 }
 
 #[rustler::nif]
 pub fn s_sum(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
-        DataType::Boolean => Ok(s.sum::<i64>().encode(env)),
-        DataType::Int64 => Ok(s.sum::<i64>().encode(env)),
-        DataType::Float64 => Ok(s.sum::<f64>().encode(env)),
-        dt => panic!("sum/1 not implemented for {:?}", dt),
+        // polars sums in the dtype of the series, so narrow integers are
+        // widened first to keep the sum from wrapping.
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64 => Ok(s.cast::<Int64Type>()?.i64()?.sum().encode(env)),
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            Ok(s.cast::<UInt64Type>()?.u64()?.sum().encode(env))
+        }
+        DataType::Float32 | DataType::Float64 => Ok(s.sum::<f64>().encode(env)),
+        dt => Err(not_implemented("sum/1", dt)),
     }
 }
 
@@ -532,11 +821,19 @@ pub fn s_sum(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
 pub fn s_min(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
-        DataType::Int64 => Ok(s.min::<i64>().encode(env)),
-        DataType::Float64 => Ok(s.min::<f64>().encode(env)),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            Ok(s.min::<i64>().encode(env))
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            Ok(s.min::<u64>().encode(env))
+        }
+        DataType::Float32 | DataType::Float64 => Ok(s.min::<f64>().encode(env)),
         DataType::Date32 => Ok(s.min::<i32>().encode(env)),
         DataType::Date64 => Ok(s.min::<i64>().encode(env)),
-        dt => panic!("min/1 not implemented for {:?}", dt),
+        DataType::Duration(_) | DataType::Time64(_) => {
+            Ok(s.cast::<Int64Type>()?.i64()?.min().encode(env))
+        }
+        dt => Err(not_implemented("min/1", dt)),
     }
 }
 
@@ -544,11 +841,19 @@ pub fn s_min(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
 pub fn s_max(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
-        DataType::Int64 => Ok(s.max::<i64>().encode(env)),
-        DataType::Float64 => Ok(s.max::<f64>().encode(env)),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            Ok(s.max::<i64>().encode(env))
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            Ok(s.max::<u64>().encode(env))
+        }
+        DataType::Float32 | DataType::Float64 => Ok(s.max::<f64>().encode(env)),
         DataType::Date32 => Ok(s.max::<i32>().encode(env)),
         DataType::Date64 => Ok(s.max::<i64>().encode(env)),
-        dt => panic!("max/1 not implemented for {:?}", dt),
+        DataType::Duration(_) | DataType::Time64(_) => {
+            Ok(s.cast::<Int64Type>()?.i64()?.max().encode(env))
+        }
+        dt => Err(not_implemented("max/1", dt)),
     }
 }
 
@@ -557,19 +862,17 @@ pub fn s_mean(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
         DataType::Boolean => Ok(s.mean().encode(env)),
-        DataType::Int64 => Ok(s.mean().encode(env)),
-        DataType::Float64 => Ok(s.mean().encode(env)),
-        dt => panic!("mean/1 not implemented for {:?}", dt),
+        dt if is_numeric(dt) => Ok(s.mean().encode(env)),
+        dt => Err(not_implemented("mean/1", dt)),
     }
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_median(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
-        DataType::Int64 => Ok(s.median().encode(env)),
-        DataType::Float64 => Ok(s.median().encode(env)),
-        dt => panic!("median/1 not implemented for {:?}", dt),
+        dt if is_numeric(dt) => Ok(s.median().encode(env)),
+        dt => Err(not_implemented("median/1", dt)),
     }
 }
 
@@ -577,9 +880,10 @@ pub fn s_median(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
 pub fn s_var(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
-        DataType::Int64 => Ok(s.i64().unwrap().var().encode(env)),
-        DataType::Float64 => Ok(s.f64().unwrap().var().encode(env)),
-        dt => panic!("var/1 not implemented for {:?}", dt),
+        DataType::Int64 => Ok(s.i64()?.var().encode(env)),
+        DataType::Float64 => Ok(s.f64()?.var().encode(env)),
+        dt if is_numeric(dt) => Ok(s.cast::<Float64Type>()?.f64()?.var().encode(env)),
+        dt => Err(not_implemented("var/1", dt)),
     }
 }
 
@@ -587,24 +891,42 @@ pub fn s_var(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
 pub fn s_std(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
     match s.dtype() {
-        DataType::Int64 => Ok(s.i64().unwrap().std().encode(env)),
-        DataType::Float64 => Ok(s.f64().unwrap().std().encode(env)),
-        dt => panic!("std/1 not implemented for {:?}", dt),
+        DataType::Int64 => Ok(s.i64()?.std().encode(env)),
+        DataType::Float64 => Ok(s.f64()?.std().encode(env)),
+        dt if is_numeric(dt) => Ok(s.cast::<Float64Type>()?.f64()?.std().encode(env)),
+        dt => Err(not_implemented("std/1", dt)),
     }
 }
 
 #[rustler::nif]
 pub fn s_get(env: Env, data: ExSeries, idx: usize) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
+    check_bounds(idx, s.len())?;
+    // Categoricals hold codes, so look the value up as a string instead.
+    let s = match s.dtype() {
+        DataType::Categorical => s.cast::<Utf8Type>()?,
+        _ => s.clone(),
+    };
     let term: Term = match s.get(idx) {
         AnyValue::Null => None::<bool>.encode(env),
         AnyValue::Boolean(v) => Some(v).encode(env),
         AnyValue::Utf8(v) => Some(v).encode(env),
+        AnyValue::Int8(v) => Some(v).encode(env),
+        AnyValue::Int16(v) => Some(v).encode(env),
+        AnyValue::Int32(v) => Some(v).encode(env),
         AnyValue::Int64(v) => Some(v).encode(env),
+        AnyValue::UInt8(v) => Some(v).encode(env),
+        AnyValue::UInt16(v) => Some(v).encode(env),
+        AnyValue::UInt32(v) => Some(v).encode(env),
+        AnyValue::UInt64(v) => Some(v).encode(env),
+        AnyValue::Float32(v) => Some(v).encode(env),
         AnyValue::Float64(v) => Some(v).encode(env),
         AnyValue::Date32(v) => Some(v).encode(env),
         AnyValue::Date64(v) => Some(v).encode(env),
-        dt => panic!("get/2 not implemented for {:?}", dt),
+        AnyValue::Time64(v, _) => Some(v).encode(env),
+        AnyValue::Duration(v, _) => Some(v).encode(env),
+        AnyValue::List(v) => encode_series(&v, env)?,
+        _ => return Err(not_implemented("get/2", s.dtype())),
     };
     Ok(term)
 }
@@ -627,7 +949,7 @@ pub fn s_cum_min(data: ExSeries, reverse: bool) -> Result<ExSeries, ExplorerErro
     Ok(ExSeries::new(s.cummin(reverse)))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_quantile(data: ExSeries, quantile: f64) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     Ok(ExSeries::new(s.quantile_as_series(quantile)?))
@@ -651,7 +973,7 @@ pub fn s_reverse(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(s.reverse()))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_n_unique(data: ExSeries) -> Result<usize, ExplorerError> {
     let s = &data.resource.0;
     Ok(s.n_unique()?)
@@ -669,18 +991,93 @@ pub fn s_cast(data: ExSeries, to_type: &str) -> Result<ExSeries, ExplorerError>
     Ok(ExSeries::new(cast(s, to_type)?))
 }
 
-pub fn cast(s: &Series, to_type: &str) -> Result<Series, PolarsError> {
+pub fn cast(s: &Series, to_type: &str) -> Result<Series, ExplorerError> {
+    let dtype = cast_dtype_from_str(to_type)?;
+    Ok(s.cast_with_dtype(&dtype)?)
+}
+
+pub(crate) fn cast_dtype_from_str(to_type: &str) -> Result<DataType, ExplorerError> {
     match to_type {
-        "float" => Ok(s.cast::<Float64Type>()?),
-        "integer" => Ok(s.cast::<Int64Type>()?),
-        "date" => Ok(s.cast::<Date32Type>()?),
-        "datetime" => Ok(s.cast::<Date64Type>()?),
-        "boolean" => Ok(s.cast::<BooleanType>()?),
-        "string" => Ok(s.cast::<Utf8Type>()?),
-        _ => panic!("Cannot cast to type"),
+        "float" | "f64" => Ok(DataType::Float64),
+        "f32" => Ok(DataType::Float32),
+        "integer" | "i64" => Ok(DataType::Int64),
+        "i8" => Ok(DataType::Int8),
+        "i16" => Ok(DataType::Int16),
+        "i32" => Ok(DataType::Int32),
+        "u8" => Ok(DataType::UInt8),
+        "u16" => Ok(DataType::UInt16),
+        "u32" => Ok(DataType::UInt32),
+        "u64" => Ok(DataType::UInt64),
+        "date" => Ok(DataType::Date32),
+        "datetime" => Ok(DataType::Date64),
+        "time" | "time64(ns)" => Ok(DataType::Time64(TimeUnit::Nanosecond)),
+        "duration" | "duration(ms)" => Ok(DataType::Duration(TimeUnit::Millisecond)),
+        "duration(ns)" => Ok(DataType::Duration(TimeUnit::Nanosecond)),
+        t if t.starts_with("datetime[") => datetime_dtype_from_str(t),
+        "boolean" => Ok(DataType::Boolean),
+        "string" => Ok(DataType::Utf8),
+        "category" | "cat" => Ok(DataType::Categorical),
+        t => Err(ExplorerError::InvalidDtype(t.to_string())),
+    }
+}
+
+/// Parses `datetime[unit]` and `datetime[unit, time_zone]` dtypes. The pinned
+/// polars only has zone-less millisecond datetimes (`Date64`), so other units
+/// and time zones are rejected rather than silently truncated or dropped.
+pub(crate) fn datetime_dtype_from_str(dtype: &str) -> Result<DataType, ExplorerError> {
+    let spec = dtype
+        .strip_prefix("datetime[")
+        .and_then(|spec| spec.strip_suffix(']'))
+        .ok_or_else(|| ExplorerError::InvalidDtype(dtype.to_string()))?;
+    let mut parts = spec.splitn(2, ',').map(str::trim);
+    match (parts.next(), parts.next()) {
+        (Some("ms"), None) => Ok(DataType::Date64),
+        (Some("ns" | "us" | "ms"), _) => Err(ExplorerError::InvalidDtype(format!(
+            "{} is not supported, only datetime[ms] without a time zone is available",
+            dtype
+        ))),
+        _ => Err(ExplorerError::InvalidDtype(dtype.to_string())),
+    }
+}
+
+pub(crate) fn check_bounds(idx: usize, len: usize) -> Result<(), ExplorerError> {
+    if idx < len {
+        Ok(())
+    } else {
+        Err(ExplorerError::OutOfBounds(format!(
+            "index {} out of bounds for length {}",
+            idx, len
+        )))
     }
 }
 
+pub(crate) fn check_indices(indices: &[u32], len: usize) -> Result<(), ExplorerError> {
+    match indices.iter().max() {
+        Some(&max) => check_bounds(max as usize, len),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn is_numeric(dtype: &DataType) -> bool {
+    matches!(
+        dtype,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+    )
+}
+
+pub(crate) fn not_implemented(function: &str, dtype: &DataType) -> ExplorerError {
+    ExplorerError::InvalidDtype(format!("{} not implemented for {:?}", function, dtype))
+}
+
 #[rustler::nif]
 pub fn s_seedable_random_indices(
     length: usize,
@@ -690,7 +1087,9 @@ pub fn s_seedable_random_indices(
 ) -> Vec<usize> {
     let mut rng: Pcg64 = SeedableRng::seed_from_u64(seed);
     let range: Vec<usize> = (0..length).collect();
-    if with_replacement {
+    if length == 0 {
+        Vec::new()
+    } else if with_replacement {
         (0..n_samples).map(|_| rng.gen_range(0..length)).collect()
     } else {
         range
@@ -0,0 +1,108 @@
+use rustler::env::{OwnedEnv, SavedTerm};
+use rustler::types::LocalPid;
+use rustler::{Encoder, Env, Term};
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::result::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::{ExOperation, ExplorerError};
+
+rustler::atoms! {
+    progress,
+}
+
+/// The context handed to work spawned with `spawn`. Long-running work calls
+/// `report` every so often, which both tells the caller how far along it is
+/// and bails out with `ExplorerError::Cancelled` once the operation has been
+/// cancelled.
+pub(crate) struct Operation {
+    pid: LocalPid,
+    env: OwnedEnv,
+    reference: SavedTerm,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Operation {
+    pub(crate) fn check_cancelled(&self) -> Result<(), ExplorerError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err(ExplorerError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sends `{:progress, reference, rows_processed}` to the caller.
+    pub(crate) fn report(&self, rows_processed: usize) -> Result<(), ExplorerError> {
+        self.check_cancelled()?;
+        self.send(|env, reference| (progress(), reference, rows_processed).encode(env));
+        Ok(())
+    }
+
+    fn send<F>(&self, message: F)
+    where
+        F: for<'b> FnOnce(Env<'b>, Term<'b>) -> Term<'b>,
+    {
+        // The reference lives in its own env, as sending clears the env the
+        // message was built in.
+        let mut msg_env = OwnedEnv::new();
+        msg_env.send_and_clear(&self.pid, |env| {
+            let reference = self
+                .env
+                .run(|ref_env| self.reference.load(ref_env).in_env(env));
+            message(env, reference)
+        });
+    }
+}
+
+/// Runs `work` on the rayon thread pool instead of a BEAM scheduler and
+/// returns a handle that can be used to cancel it. Once `work` is done,
+/// `{reference, result}` is sent to the calling process, where `result` is
+/// `{:ok, value}` or `{:error, reason}`, just like the return value of the
+/// synchronous NIF. A cancelled operation always replies with
+/// `{:error, {:cancelled, _}}`, and a panic in `work` replies with
+/// `{:error, {:internal, message}}` instead of taking down the VM.
+///
+/// The operation only stops when it is cancelled explicitly, so the handle
+/// does not need to be kept alive to receive the result.
+pub(crate) fn spawn<'a, F, T>(env: Env<'a>, reference: Term<'a>, work: F) -> ExOperation
+where
+    F: FnOnce(&Operation) -> Result<T, ExplorerError> + Send + 'static,
+    T: Encoder,
+{
+    let handle = ExOperation::new();
+    let owned_env = OwnedEnv::new();
+    let reference = owned_env.save(reference);
+    let operation = Operation {
+        pid: env.pid(),
+        env: owned_env,
+        reference,
+        cancelled: handle.resource.0.clone(),
+    };
+
+    rayon::spawn(move || {
+        // rustler catches panics in NIFs, but nothing does on the rayon pool.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            operation.check_cancelled().and_then(|_| work(&operation))
+        }))
+        .unwrap_or_else(|payload| Err(ExplorerError::Internal(panic_message(payload))));
+        // Work that cannot be interrupted, like a join, only notices that it
+        // was cancelled once it is done, and its result is dropped then.
+        let result = operation.check_cancelled().and(result);
+        operation.send(|env, reference| (reference, result).encode(env));
+    });
+
+    handle
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "async operation panicked".to_string(),
+        },
+    }
+}
@@ -8,6 +8,20 @@ defmodule Explorer.DataFrameTest do
     {:ok, df: Explorer.Datasets.fossil_fuels()}
   end
 
+  describe "read_csv!/2" do
+    test "raises with the formatted error for a missing file" do
+      assert_raise RuntimeError, "io: :enoent", fn -> DF.read_csv!("missing.csv") end
+    end
+  end
+
+  describe "write_csv!/3" do
+    test "raises with the formatted error for a missing directory", %{df: df} do
+      assert_raise RuntimeError, "io: :enoent", fn ->
+        DF.write_csv!(df, Path.join(["missing", "dir", "out.csv"]))
+      end
+    end
+  end
+
   describe "filter/2" do
     test "raises with mask of invalid length", %{df: df} do
       assert_raise ArgumentError,
@@ -0,0 +1,949 @@
+defmodule Explorer.PolarsBackend.NativeTest do
+  use ExUnit.Case, async: true
+
+  alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
+
+  @constructors [
+    s_new_bool: [true, false, nil],
+    s_new_date32: ["2021-01-01", nil, "2021-01-03"],
+    s_new_date64: ["2021-01-01T00:00:00", nil, "2021-01-03T00:00:00"],
+    s_new_f32: [1.0, nil, 3.0],
+    s_new_f64: [1.0, nil, 3.0],
+    s_new_i8: [1, nil, 3],
+    s_new_i16: [1, nil, 3],
+    s_new_i32: [1, nil, 3],
+    s_new_i64: [1, nil, 3],
+    s_new_u8: [1, nil, 3],
+    s_new_u16: [1, nil, 3],
+    s_new_u32: [1, nil, 3],
+    s_new_u64: [1, nil, 3],
+    s_new_str: ["a", nil, "c"]
+  ]
+
+  @reducers [:s_sum, :s_min, :s_max, :s_mean, :s_median, :s_var, :s_std, :s_to_list]
+
+  defp assert_no_raise(result) do
+    assert match?({:ok, _}, result) or match?({:error, {kind, _}} when is_atom(kind), result)
+  end
+
+  defp tmp_path(name) do
+    path = Path.join(System.tmp_dir!(), name)
+    on_exit(fn -> File.rm_rf(path) end)
+    path
+  end
+
+  defp column(df, name) do
+    {:ok, s} = Native.df_column(df, name)
+    {:ok, list} = Native.s_to_list(s)
+    list
+  end
+
+  defp assert_json_columns(df) do
+    assert column(df, "a") == [1, nil, 3]
+    assert column(df, "b") == [1.5, 2.5, nil]
+    assert column(df, "c") == ["x", nil, "z"]
+  end
+
+  for {constructor, values} <- @constructors do
+    describe "#{constructor}" do
+      setup do
+        {:ok, series} = apply(Native, unquote(constructor), ["s", unquote(values)])
+        {:ok, series: series}
+      end
+
+      for reducer <- @reducers do
+        test "#{reducer} returns instead of raising", %{series: series} do
+          assert_no_raise(apply(Native, unquote(reducer), [series]))
+        end
+      end
+
+      test "s_get returns an error for an out of bounds index", %{series: series} do
+        assert_no_raise(Native.s_get(series, 0))
+        assert {:error, {:out_of_bounds, _}} = Native.s_get(series, 10)
+      end
+
+      test "s_cast returns an error for an unknown dtype", %{series: series} do
+        assert {:error, {:invalid_dtype, "unknown"}} = Native.s_cast(series, "unknown")
+      end
+    end
+  end
+
+  test "df_read_csv returns an error for an unknown dtype" do
+    path = Path.join(System.tmp_dir!(), "native_test_dtypes.csv")
+    File.write!(path, "a,b\n1,2\n")
+    on_exit(fn -> File.rm(path) end)
+
+    assert {:error, {:invalid_dtype, "unknown"}} =
+             Native.df_read_csv(path, Shared.csv_read_options(dtypes: [{"a", "unknown"}]))
+  end
+
+  describe "df_read_csv" do
+    test "applies the quote, comment, null and footer options" do
+      path = tmp_path("native_test_read_csv_options.csv")
+      File.write!(path, "'a';'b'\n1;\n# note\nNA;'x;y'\n3;z\ntotal;3\n")
+
+      options =
+        Shared.csv_read_options(
+          delimiter: ";",
+          quote_char: "'",
+          comment_char: "#",
+          column_null_values: [{"a", "NA"}, {"b", ""}],
+          skip_footer: 1
+        )
+
+      assert {:ok, df} = Native.df_read_csv(path, options)
+      assert column(df, "a") == [1, nil, 3]
+      assert column(df, "b") == [nil, "x;y", "z"]
+    end
+
+    test "skips and limits rows and selects columns" do
+      path = tmp_path("native_test_read_csv_rows.csv")
+      File.write!(path, "exported today\na,b,c\n1,2,3\n4,5,6\n7,8,9\n")
+      options = Shared.csv_read_options(skip_rows: 1, stop_after_n_rows: 2, columns: ["a", "c"])
+
+      assert {:ok, df} = Native.df_read_csv(path, options)
+      assert {:ok, ["a", "c"]} = Native.df_columns(df)
+      assert column(df, "c") == [3, 6]
+    end
+
+    test "returns an error for an empty delimiter" do
+      path = tmp_path("native_test_read_csv_delimiter.csv")
+      File.write!(path, "a\n1\n")
+      options = Shared.csv_read_options(delimiter: "")
+      assert {:error, {:other, _}} = Native.df_read_csv(path, options)
+    end
+  end
+
+  describe "integer and float widths" do
+    for {constructor, dtype} <- [
+          s_new_i8: "i8",
+          s_new_i16: "i16",
+          s_new_i32: "i32",
+          s_new_u8: "u8",
+          s_new_u16: "u16",
+          s_new_u32: "u32",
+          s_new_u64: "u64"
+        ] do
+      test "#{constructor} keeps its width through reducers and casts" do
+        {:ok, s} = apply(Native, unquote(constructor), ["s", [1, nil, 3]])
+        assert {:ok, unquote(dtype)} = Native.s_dtype(s)
+        assert {:ok, [1, nil, 3]} = Native.s_to_list(s)
+        assert {:ok, 4} = Native.s_sum(s)
+        assert {:ok, 1} = Native.s_min(s)
+        assert {:ok, 3} = Native.s_max(s)
+        assert {:ok, 2.0} = Native.s_mean(s)
+        assert {:ok, 3} = Native.s_get(s, 2)
+
+        assert {:ok, cast} = Native.s_cast(s, "f32")
+        assert {:ok, "f32"} = Native.s_dtype(cast)
+        assert {:ok, [1.0, nil, 3.0]} = Native.s_to_list(cast)
+      end
+    end
+
+    for {constructor, max} <- [
+          s_new_i8: 127,
+          s_new_i16: 32_767,
+          s_new_i32: 2_147_483_647,
+          s_new_u8: 255,
+          s_new_u16: 65_535,
+          s_new_u32: 4_294_967_295
+        ] do
+      test "#{constructor} sums past the maximum of its width" do
+        {:ok, s} = apply(Native, unquote(constructor), ["s", [unquote(max), nil, unquote(max)]])
+        assert {:ok, unquote(max * 2)} = Native.s_sum(s)
+      end
+    end
+  end
+
+  describe "datetime dtypes" do
+    test "s_cast accepts zone-less millisecond datetimes" do
+      {:ok, s} = Native.s_new_date64("s", ["2021-01-01T00:00:00", nil])
+      assert {:ok, cast} = Native.s_cast(s, "datetime[ms]")
+      assert {:ok, "date64(ms)"} = Native.s_dtype(cast)
+    end
+
+    test "s_cast rejects other time units and time zones" do
+      {:ok, s} = Native.s_new_date64("s", ["2021-01-01T00:00:00", nil])
+      assert {:error, {:invalid_dtype, _}} = Native.s_cast(s, "datetime[us]")
+      assert {:error, {:invalid_dtype, _}} = Native.s_cast(s, "datetime[ms, Europe/Lisbon]")
+    end
+  end
+
+  describe "async variants" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [3, 1, 2])
+      {:ok, b} = Native.s_new_str("b", ["c", "a", "b"])
+      {:ok, df} = Native.df_new([a, b])
+      {:ok, df: df}
+    end
+
+    test "df_sort_async sends the sorted frame back", %{df: df} do
+      assert {:ok, sorted} = Shared.apply_native_async(:df_sort_async, [df, "a", false])
+      assert {:ok, a} = Native.df_column(sorted, "a")
+      assert {:ok, [1, 2, 3]} = Native.s_to_list(a)
+    end
+
+    test "df_to_csv_file_async reports progress", %{df: df} do
+      path = Path.join(System.tmp_dir!(), "native_test_async.csv")
+      on_exit(fn -> File.rm(path) end)
+      args = [df, path, Shared.csv_write_options(), nil]
+
+      assert {:ok, _} =
+               Shared.apply_native_async(:df_to_csv_file_async, args,
+                 on_progress: &send(self(), {:rows, &1})
+               )
+
+      assert_received {:rows, 3}
+      assert File.read!(path) == "a,b\n3,c\n1,a\n2,b\n"
+    end
+
+    test "df_cancel_async cancels an operation before it completes" do
+      # Opening a FIFO blocks until it has a writer, which holds the reader
+      # until the operation has been cancelled.
+      path = tmp_path("native_test_cancel.csv")
+      {_, 0} = System.cmd("mkfifo", [path])
+      ref = make_ref()
+
+      assert {:ok, operation} =
+               Native.df_read_csv_async(ref, path, Shared.csv_read_options())
+
+      assert Native.df_cancel_async(operation)
+      File.write!(path, "a,b\n1,x\n")
+
+      assert_receive {^ref, {:error, {:cancelled, _}}}
+      refute Native.df_cancel_async(operation)
+    end
+
+    test "an operation whose handle is dropped still replies" do
+      path = tmp_path("native_test_dropped.csv")
+      {_, 0} = System.cmd("mkfifo", [path])
+      ref = make_ref()
+
+      assert {:ok, _operation} =
+               Native.df_read_csv_async(ref, path, Shared.csv_read_options())
+
+      :erlang.garbage_collect()
+      File.write!(path, "a,b\n1,x\n")
+
+      assert_receive {^ref, {:ok, df}}
+      assert column(df, "a") == [1]
+    end
+
+    test "df_write_parquet_async reports progress per row group", %{df: df} do
+      path = tmp_path("native_test_async.parquet")
+      args = [df, path, "snappy", 2, true]
+
+      assert {:ok, _} =
+               Shared.apply_native_async(:df_write_parquet_async, args,
+                 on_progress: &send(self(), {:rows, &1})
+               )
+
+      assert_received {:rows, 2}
+      assert_received {:rows, 3}
+      assert {:ok, read} = Native.df_read_parquet(path, nil, nil, [])
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+
+    test "df_join_async sends errors back as messages", %{df: df} do
+      assert {:error, {_kind, _details}} =
+               Shared.apply_native_async(:df_join_async, [df, df, ["x"], ["x"], "inner"])
+    end
+  end
+
+  describe "df_write_parquet" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, nil, 3])
+      {:ok, b} = Native.s_new_f64("b", [1.5, 2.5, nil])
+      {:ok, c} = Native.s_new_str("c", ["x", nil, "z"])
+      {:ok, df} = Native.df_new([a, b, c])
+      {:ok, df: df}
+    end
+
+    for compression <- ["uncompressed", "snappy", "gzip", "zstd", "lz4"] do
+      test "round trips through df_read_parquet with #{compression}", %{df: df} do
+        path = tmp_path("native_test_write_parquet_#{unquote(compression)}.parquet")
+        assert {:ok, _} = Native.df_write_parquet(df, path, unquote(compression), nil, true)
+        assert {:ok, read} = Native.df_read_parquet(path, nil, nil, [])
+        assert {:ok, true} = Native.df_frame_equal(df, read, true)
+      end
+    end
+
+    test "writes row groups of the given size", %{df: df} do
+      path = tmp_path("native_test_write_parquet_row_groups.parquet")
+      assert {:ok, _} = Native.df_write_parquet(df, path, "snappy", 1, false)
+      assert {:ok, %{num_rows: 3, num_row_groups: 3}} = Native.df_parquet_metadata(path)
+    end
+
+    test "rejects unknown compressions", %{df: df} do
+      path = tmp_path("native_test_write_parquet_unknown.parquet")
+      assert {:error, {:other, _}} = Native.df_write_parquet(df, path, "brotli", nil, true)
+    end
+  end
+
+  describe "df_read_parquet" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, 2, 3, 4])
+      {:ok, b} = Native.s_new_str("b", ["w", "x", "y", "z"])
+      {:ok, df} = Native.df_new([a, b])
+      path = tmp_path("native_test_read_parquet.parquet")
+      assert {:ok, _} = Native.df_write_parquet(df, path, "snappy", 2, true)
+      {:ok, path: path}
+    end
+
+    test "projects columns and limits rows", %{path: path} do
+      assert {:ok, df} = Native.df_read_parquet(path, ["b"], nil, [])
+      assert {:ok, ["b"]} = Native.df_columns(df)
+
+      assert {:ok, df} = Native.df_read_parquet(path, nil, 3, [])
+      assert {:ok, 3} = Native.df_height(df)
+    end
+
+    test "prunes row groups with predicates", %{path: path} do
+      assert {:ok, %{num_row_groups: 2}} = Native.df_parquet_metadata(path)
+      assert {:ok, df} = Native.df_read_parquet(path, nil, nil, [{"a", "lt_eq", 2}])
+      assert column(df, "a") == [1, 2]
+    end
+
+    test "filters on columns that are not selected", %{path: path} do
+      assert {:ok, df} = Native.df_read_parquet(path, ["b"], nil, [{"a", "gt", 2}])
+      assert {:ok, ["b"]} = Native.df_columns(df)
+      assert column(df, "b") == ["y", "z"]
+    end
+
+    test "keeps row groups whose statistics are unsigned or dates" do
+      big = 9_223_372_036_854_775_808
+      {:ok, u} = Native.s_new_u64("u", [1, 2, big + 1, big + 2])
+
+      {:ok, d} =
+        Native.s_new_date64("d", [
+          "2021-01-01T00:00:00",
+          "2021-01-02T00:00:00",
+          "2021-01-03T00:00:00",
+          "2021-01-04T00:00:00"
+        ])
+
+      {:ok, df} = Native.df_new([u, d])
+      path = tmp_path("native_test_read_parquet_stats.parquet")
+      assert {:ok, _} = Native.df_write_parquet(df, path, "snappy", 2, true)
+
+      assert {:ok, read} = Native.df_read_parquet(path, ["u"], nil, [{"u", "gt", 2}])
+      assert column(read, "u") == [big + 1, big + 2]
+
+      # 2021-01-02T00:00:00 in milliseconds.
+      assert {:ok, read} =
+               Native.df_read_parquet(path, ["u"], nil, [{"d", "gt", 1_609_545_600_000}])
+
+      assert column(read, "u") == [big + 1, big + 2]
+    end
+
+    test "rejects predicates of the wrong dtype", %{path: path} do
+      assert {:error, {:invalid_dtype, _}} =
+               Native.df_read_parquet(path, nil, nil, [{"b", "gt", 1}])
+
+      assert {:error, {:invalid_dtype, _}} =
+               Native.df_read_parquet(path, nil, nil, [{"a", "eq", "x"}])
+    end
+  end
+
+  describe "df_write_ipc" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, nil, 3])
+      {:ok, b} = Native.s_new_bool("b", [true, false, nil])
+      {:ok, c} = Native.s_new_str("c", ["x", nil, "z"])
+      {:ok, d} = Native.s_new_date32("d", ["2021-01-01", nil, "2021-01-03"])
+      {:ok, e} = Native.s_new_date64("e", ["2021-01-01T00:00:00", nil, "2021-01-03T00:00:00"])
+      {:ok, df} = Native.df_new([a, b, c, d, e])
+      {:ok, df: df}
+    end
+
+    test "round trips through df_read_ipc", %{df: df} do
+      path = tmp_path("native_test_write_ipc.arrow")
+      assert {:ok, _} = Native.df_write_ipc(df, path)
+      assert {:ok, read} = Native.df_read_ipc(path, nil)
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+
+    test "round trips through df_read_ipc_stream", %{df: df} do
+      path = tmp_path("native_test_write_ipc_stream.arrows")
+      assert {:ok, _} = Native.df_write_ipc_stream(df, path)
+      assert {:ok, read} = Native.df_read_ipc_stream(path, nil)
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+
+    test "projects columns on read", %{df: df} do
+      path = tmp_path("native_test_write_ipc_projection.arrow")
+      assert {:ok, _} = Native.df_write_ipc(df, path)
+      assert {:ok, read} = Native.df_read_ipc(path, ["c", "a"])
+      assert {:ok, ["c", "a"]} = Native.df_columns(read)
+      assert column(read, "a") == [1, nil, 3]
+
+      path = tmp_path("native_test_write_ipc_stream_projection.arrows")
+      assert {:ok, _} = Native.df_write_ipc_stream(df, path)
+      assert {:ok, read} = Native.df_read_ipc_stream(path, ["b"])
+      assert {:ok, ["b"]} = Native.df_columns(read)
+      assert {:error, {:column_not_found, "f"}} = Native.df_read_ipc_stream(path, ["f"])
+    end
+
+    test "keeps the schema of empty streams" do
+      {:ok, a} = Native.s_new_i64("a", [])
+      {:ok, df} = Native.df_new([a])
+      path = tmp_path("native_test_write_ipc_stream_empty.arrows")
+      assert {:ok, _} = Native.df_write_ipc_stream(df, path)
+      assert {:ok, read} = Native.df_read_ipc_stream(path, nil)
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+  end
+
+  describe "df_load_*" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, nil, 3])
+      {:ok, b} = Native.s_new_str("b", ["x", "y", nil])
+      {:ok, df} = Native.df_new([a, b])
+      {:ok, df: df}
+    end
+
+    test "df_load_csv reads a binary" do
+      csv = "a,b\n1,x\nNA,y\n3,NA\n"
+      assert {:ok, df} = Native.df_load_csv(csv, Shared.csv_read_options())
+
+      assert column(df, "a") == [1, nil, 3]
+      assert column(df, "b") == ["x", "y", nil]
+    end
+
+    test "df_load_parquet reads the bytes of a parquet file", %{df: df} do
+      path = tmp_path("native_test_load_parquet.parquet")
+      assert {:ok, _} = Native.df_write_parquet(df, path, "snappy", nil, true)
+      assert {:ok, read} = Native.df_load_parquet(File.read!(path), nil, nil, [])
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+
+      assert {:ok, read} = Native.df_load_parquet(File.read!(path), ["b"], 2, [])
+      assert column(read, "b") == ["x", "y"]
+    end
+
+    test "df_load_ipc and df_load_ipc_stream read the bytes of IPC files", %{df: df} do
+      path = tmp_path("native_test_load_ipc.arrow")
+      assert {:ok, _} = Native.df_write_ipc(df, path)
+      assert {:ok, read} = Native.df_load_ipc(File.read!(path), nil)
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+
+      path = tmp_path("native_test_load_ipc_stream.arrows")
+      assert {:ok, _} = Native.df_write_ipc_stream(df, path)
+      assert {:ok, read} = Native.df_load_ipc_stream(File.read!(path), ["a"])
+      assert column(read, "a") == [1, nil, 3]
+    end
+
+    test "returns an error for bytes that are not in the format" do
+      assert {:error, {_kind, _}} = Native.df_load_parquet("not parquet", nil, nil, [])
+      assert {:error, {_kind, _}} = Native.df_load_ipc("not ipc", nil)
+    end
+  end
+
+  describe "df_dump_*" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, nil, 3])
+      {:ok, b} = Native.s_new_date32("b", ["2021-01-01", "2021-01-02", nil])
+      {:ok, df} = Native.df_new([a, b])
+      {:ok, df: df}
+    end
+
+    test "df_dump_parquet round trips through df_load_parquet", %{df: df} do
+      assert {:ok, binary} = Native.df_dump_parquet(df, "zstd", 2, true)
+      assert is_binary(binary)
+      assert {:ok, read} = Native.df_load_parquet(binary, nil, nil, [])
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+
+    test "df_dump_parquet takes the options of df_write_parquet", %{df: df} do
+      path = tmp_path("native_test_dump_parquet.parquet")
+      assert {:ok, binary} = Native.df_dump_parquet(df, "snappy", 1, true)
+      File.write!(path, binary)
+      assert {:ok, %{num_rows: 3, num_row_groups: 3}} = Native.df_parquet_metadata(path)
+
+      assert {:error, {:other, _}} = Native.df_dump_parquet(df, "brotli", nil, true)
+    end
+
+    test "df_dump_ipc and df_dump_ipc_stream round trip through the loaders", %{df: df} do
+      assert {:ok, binary} = Native.df_dump_ipc(df)
+      assert {:ok, read} = Native.df_load_ipc(binary, nil)
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+
+      assert {:ok, binary} = Native.df_dump_ipc_stream(df)
+      assert {:ok, read} = Native.df_load_ipc_stream(binary, nil)
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+  end
+
+  describe "JSON" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, nil, 3])
+      {:ok, b} = Native.s_new_f64("b", [1.5, 2.5, nil])
+      {:ok, c} = Native.s_new_str("c", ["x", nil, "z"])
+      {:ok, df} = Native.df_new([a, b, c])
+      {:ok, df: df}
+    end
+
+    test "df_to_ndjson round trips through df_load_ndjson", %{df: df} do
+      assert {:ok, ndjson} = Native.df_to_ndjson(df)
+      assert length(String.split(ndjson, "\n", trim: true)) == 3
+      assert {:ok, read} = Native.df_load_ndjson(ndjson, 100, nil, nil)
+      assert_json_columns(read)
+    end
+
+    test "df_to_ndjson_file round trips through df_read_ndjson", %{df: df} do
+      path = tmp_path("native_test_ndjson.ndjson")
+      assert {:ok, _} = Native.df_to_ndjson_file(df, path)
+      assert {:ok, read} = Native.df_read_ndjson(path, nil, nil, nil)
+      assert_json_columns(read)
+    end
+
+    for orient <- ["records", "columns"] do
+      test "df_to_json round trips #{orient} through df_load_json", %{df: df} do
+        assert {:ok, json} = Native.df_to_json(df, unquote(orient))
+        assert {:ok, read} = Native.df_load_json(json, nil, nil, nil)
+        assert_json_columns(read)
+      end
+
+      test "df_to_json_file round trips #{orient} through df_read_json", %{df: df} do
+        path = tmp_path("native_test_json_#{unquote(orient)}.json")
+        assert {:ok, _} = Native.df_to_json_file(df, path, unquote(orient))
+        assert {:ok, read} = Native.df_read_json(path, nil, nil, nil)
+        assert_json_columns(read)
+      end
+    end
+
+    test "reads the given dtypes and columns" do
+      ndjson = ~s({"a": 1, "b": "x"}\n{"a": 2, "b": "y"}\n)
+
+      assert {:ok, df} = Native.df_load_ndjson(ndjson, nil, [{"a", "f64"}], nil)
+      assert {:ok, ["a"]} = Native.df_columns(df)
+      assert column(df, "a") == [1.0, 2.0]
+
+      assert {:ok, df} = Native.df_load_ndjson(ndjson, nil, nil, ["b"])
+      assert {:ok, ["b"]} = Native.df_columns(df)
+    end
+
+    test "rejects unknown orientations", %{df: df} do
+      assert {:error, {:other, _}} = Native.df_to_json(df, "split")
+    end
+  end
+
+  describe "df_read_csv_batched" do
+    setup do
+      path = tmp_path("native_test_read_csv_batched.csv")
+      File.write!(path, "a,b\n1,v\n2,w\n3,x\n4,y\n5,z\n")
+      {:ok, path: path}
+    end
+
+    test "reads batches of up to n rows until the file is exhausted", %{path: path} do
+      assert {:ok, reader} = Native.df_read_csv_batched(path, Shared.csv_read_options())
+      assert {:ok, df} = Native.df_next_csv_batch(reader, 2)
+      assert column(df, "a") == [1, 2]
+      assert {:ok, df} = Native.df_next_csv_batch(reader, 2)
+      assert column(df, "b") == ["x", "y"]
+      assert {:ok, df} = Native.df_next_csv_batch(reader, 2)
+      assert column(df, "a") == [5]
+      assert {:ok, nil} = Native.df_next_csv_batch(reader, 2)
+      assert {:ok, nil} = Native.df_next_csv_batch(reader, 2)
+    end
+
+    test "keeps the dtypes of the first batch", %{path: path} do
+      File.write!(path, "a\n1\n2\nNA\n")
+
+      assert {:ok, reader} = Native.df_read_csv_batched(path, Shared.csv_read_options())
+      assert {:ok, first} = Native.df_next_csv_batch(reader, 2)
+      assert {:ok, last} = Native.df_next_csv_batch(reader, 2)
+      assert {:ok, dtypes} = Native.df_dtypes(first)
+      assert {:ok, ^dtypes} = Native.df_dtypes(last)
+      assert column(last, "a") == [nil]
+    end
+
+    test "returns an error for a missing file" do
+      path = Path.join(System.tmp_dir!(), "native_test_missing.csv")
+      options = Shared.csv_read_options()
+      assert {:error, {:io, :enoent}} = Native.df_read_csv_batched(path, options)
+    end
+  end
+
+  describe "df_open_csv_writer" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, 2])
+      {:ok, b} = Native.s_new_str("b", ["x", "y"])
+      {:ok, df} = Native.df_new([a, b])
+      {:ok, df: df, path: tmp_path("native_test_csv_writer.csv")}
+    end
+
+    test "writes the header once and appends frames", %{df: df, path: path} do
+      assert {:ok, writer} = Native.df_open_csv_writer(path, Shared.csv_write_options(), false)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert File.read!(path) == "a,b\n1,x\n2,y\n1,x\n2,y\n"
+
+      assert {:ok, writer} = Native.df_open_csv_writer(path, Shared.csv_write_options(), true)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert File.read!(path) == "a,b\n1,x\n2,y\n1,x\n2,y\n1,x\n2,y\n"
+    end
+
+    test "truncates the file unless appending", %{df: df, path: path} do
+      File.write!(path, "old contents\n")
+      options = Shared.csv_write_options(delimiter: ?;)
+      assert {:ok, writer} = Native.df_open_csv_writer(path, options, false)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert File.read!(path) == "a;b\n1;x\n2;y\n"
+    end
+
+    test "returns an error for frames with another schema", %{df: df, path: path} do
+      {:ok, a} = Native.s_new_str("a", ["1"])
+      {:ok, b} = Native.s_new_str("b", ["z"])
+      {:ok, other} = Native.df_new([a, b])
+
+      assert {:ok, writer} = Native.df_open_csv_writer(path, Shared.csv_write_options(), false)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:error, {:schema_mismatch, _}} = Native.df_write_csv_batch(writer, other)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert File.read!(path) == "a,b\n1,x\n2,y\n"
+
+      assert {:ok, writer} = Native.df_open_csv_writer(path, Shared.csv_write_options(), true)
+      assert {:ok, b} = Native.df_select(df, ["b"])
+      assert {:error, {:schema_mismatch, _}} = Native.df_write_csv_batch(writer, b)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+    end
+
+    test "writes like df_to_csv with the same options", %{path: path} do
+      {:ok, a} = Native.s_new_f64("a", [1.26, nil])
+      {:ok, b} = Native.s_new_date32("b", ["2021-01-02", nil])
+      {:ok, df} = Native.df_new([a, b])
+
+      options =
+        Shared.csv_write_options(
+          quote_style: "always",
+          null_value: "NA",
+          date_format: "%d/%m/%Y",
+          float_precision: 1
+        )
+
+      assert {:ok, writer} = Native.df_open_csv_writer(path, options, false)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert {:ok, csv} = Native.df_to_csv(df, options)
+      assert File.read!(path) == csv
+    end
+
+    test "appends to a file whose header quotes the delimiter", %{path: path} do
+      {:ok, a} = Native.s_new_i64("a,b", [1])
+      {:ok, c} = Native.s_new_str("c", ["x"])
+      {:ok, df} = Native.df_new([a, c])
+      File.write!(path, "\"a,b\",c\n")
+
+      assert {:ok, writer} = Native.df_open_csv_writer(path, Shared.csv_write_options(), true)
+      assert {:ok, _} = Native.df_write_csv_batch(writer, df)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert File.read!(path) == "\"a,b\",c\n1,x\n"
+    end
+
+    test "returns an error once closed", %{df: df, path: path} do
+      assert {:ok, writer} = Native.df_open_csv_writer(path, Shared.csv_write_options(), false)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert {:ok, _} = Native.df_close_csv_writer(writer)
+      assert {:error, {:other, _}} = Native.df_write_csv_batch(writer, df)
+    end
+  end
+
+  describe "df_to_csv" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, nil])
+      {:ok, b} = Native.s_new_f64("b", [1.5, 2.26])
+      {:ok, c} = Native.s_new_str("c", ["a", "b,c"])
+      {:ok, d} = Native.s_new_date32("d", ["2021-01-02", nil])
+      {:ok, e} = Native.s_new_date64("e", ["2021-01-02T03:04:05", nil])
+      {:ok, df} = Native.df_new([a, b, c, d, e])
+      {:ok, df: df}
+    end
+
+    test "writes with the default options", %{df: df} do
+      assert {:ok, csv} = Native.df_to_csv(df, Shared.csv_write_options())
+
+      assert csv ==
+               "a,b,c,d,e\n1,1.5,a,2021-01-02,2021-01-02T03:04:05.000\n,2.26,\"b,c\",,\n"
+    end
+
+    test "applies quoting, null, date and float options", %{df: df} do
+      options =
+        Shared.csv_write_options(
+          quote_style: "always",
+          null_value: "NULL",
+          date_format: "%d/%m/%Y",
+          datetime_format: "%Y-%m-%d %H:%M",
+          float_precision: 1
+        )
+
+      assert {:ok, csv} = Native.df_to_csv(df, options)
+
+      assert csv ==
+               ~s("a","b","c","d","e"\n) <>
+                 ~s("1","1.5","a","02/01/2021","2021-01-02 03:04"\n) <>
+                 ~s(NULL,"2.3","b,c",NULL,NULL\n)
+    end
+
+    test "writes f32 values as f32" do
+      {:ok, f} = Native.s_new_f32("f", [0.1, nil])
+      {:ok, df} = Native.df_new([f])
+      assert {:ok, "f\n0.1\n\n"} = Native.df_to_csv(df, Shared.csv_write_options())
+    end
+
+    test "rejects date and datetime formats it cannot apply", %{df: df} do
+      for options <- [[date_format: "%Q"], [datetime_format: "%Q"], [date_format: "%H:%M"]] do
+        assert {:error, {:other, _}} = Native.df_to_csv(df, Shared.csv_write_options(options))
+      end
+    end
+
+    test "df_to_csv_file takes the same options", %{df: df} do
+      path = tmp_path("native_test_to_csv_file.csv")
+      options = Shared.csv_write_options(has_headers: false, delimiter: ?;, null_value: "NA")
+      assert {:ok, _} = Native.df_to_csv_file(df, path, options, nil)
+      assert File.read!(path) == "1;1.5;a;2021-01-02;2021-01-02T03:04:05.000\nNA;2.26;b,c;NA;NA\n"
+    end
+
+    test "rejects unknown quote styles", %{df: df} do
+      options = Shared.csv_write_options(quote_style: "sometimes")
+      assert {:error, {:other, _}} = Native.df_to_csv(df, options)
+    end
+  end
+
+  describe "compressed input" do
+    setup do
+      {:ok, a} = Native.s_new_i64("a", [1, 2, 3])
+      {:ok, b} = Native.s_new_str("b", ["x", "y", "z"])
+      {:ok, df} = Native.df_new([a, b])
+      {:ok, df: df}
+    end
+
+    for {compression, magic} <- [
+          {"gzip", <<0x1F, 0x8B>>},
+          {"zstd", <<0x28, 0xB5, 0x2F, 0xFD>>},
+          {"bzip2", "BZh"}
+        ] do
+      test "CSV readers detect #{compression} from the magic bytes", %{df: df} do
+        path = tmp_path("native_test_compressed_#{unquote(compression)}.data")
+        options = Shared.csv_write_options()
+        assert {:ok, _} = Native.df_to_csv_file(df, path, options, unquote(compression))
+        assert <<unquote(magic)::binary, _::binary>> = File.read!(path)
+
+        options = Shared.csv_read_options()
+        assert {:ok, read} = Native.df_read_csv(path, options)
+        assert {:ok, true} = Native.df_frame_equal(df, read, true)
+
+        assert {:ok, read} = Native.df_load_csv(File.read!(path), options)
+        assert {:ok, true} = Native.df_frame_equal(df, read, true)
+
+        assert {:ok, reader} = Native.df_read_csv_batched(path, options)
+        assert {:ok, read} = Native.df_next_csv_batch(reader, 10)
+        assert {:ok, true} = Native.df_frame_equal(df, read, true)
+      end
+    end
+
+    test "JSON readers decompress their input" do
+      path = tmp_path("native_test_compressed.ndjson")
+      File.write!(path, :zlib.gzip(~s({"a": 1}\n{"a": 2}\n)))
+      assert {:ok, df} = Native.df_read_ndjson(path, nil, nil, nil)
+      assert column(df, "a") == [1, 2]
+
+      assert {:ok, df} = Native.df_load_json(:zlib.gzip(~s([{"a": 3}])), nil, nil, nil)
+      assert column(df, "a") == [3]
+    end
+
+    test "rejects unknown output compressions", %{df: df} do
+      path = tmp_path("native_test_compressed_unknown.csv")
+      options = Shared.csv_write_options()
+      assert {:error, {:other, _}} = Native.df_to_csv_file(df, path, options, "xz")
+    end
+  end
+
+  describe "df_read_dataset" do
+    setup do
+      dir = tmp_path("native_test_dataset")
+      File.mkdir_p!(dir)
+      {:ok, dir: dir}
+    end
+
+    test "stacks the CSV files matched by a glob with the given options", %{dir: dir} do
+      File.write!(Path.join(dir, "part-1.csv"), "a;b\n1;x\n")
+      File.write!(Path.join(dir, "part-2.csv"), "a;b\nNA;y\n")
+      File.write!(Path.join(dir, "other.txt"), "not,csv\n")
+      options = Shared.csv_read_options(delimiter: ";")
+
+      assert {:ok, df} =
+               Native.df_read_dataset([Path.join(dir, "part-*.csv")], "csv", options, "file")
+
+      assert column(df, "a") == [1, nil]
+      assert column(df, "b") == ["x", "y"]
+      assert Enum.map(column(df, "file"), &Path.basename/1) == ["part-1.csv", "part-2.csv"]
+    end
+
+    test "widens CSV columns that hold floats in a later file", %{dir: dir} do
+      File.write!(Path.join(dir, "part-1.csv"), "a,b\n1,x\n")
+      File.write!(Path.join(dir, "part-2.csv"), "a,b\n2.5,y\n")
+      glob = Path.join(dir, "part-*.csv")
+
+      assert {:ok, df} = Native.df_read_dataset([glob], "csv", Shared.csv_read_options(), nil)
+      assert {:ok, ["f64", "str"]} = Native.df_dtypes(df)
+      assert column(df, "a") == [1.0, 2.5]
+    end
+
+    test "widens the dtypes of earlier parquet files to a later one", %{dir: dir} do
+      {:ok, a} = Native.s_new_i32("a", [1])
+      {:ok, first} = Native.df_new([a])
+      {:ok, a} = Native.s_new_i64("a", [2])
+      {:ok, second} = Native.df_new([a])
+
+      for {df, name} <- [{first, "1.parquet"}, {second, "2.parquet"}] do
+        assert {:ok, _} = Native.df_write_parquet(df, Path.join(dir, name), "snappy", nil, true)
+      end
+
+      glob = Path.join(dir, "*.parquet")
+      assert {:ok, df} = Native.df_read_dataset([glob], "parquet", nil, nil)
+      assert {:ok, ["i64"]} = Native.df_dtypes(df)
+      assert column(df, "a") == [1, 2]
+    end
+
+    test "widens the dtypes of later parquet files to the first one", %{dir: dir} do
+      {:ok, a} = Native.s_new_i64("a", [1])
+      {:ok, first} = Native.df_new([a])
+      {:ok, a} = Native.s_new_i32("a", [2])
+      {:ok, second} = Native.df_new([a])
+
+      for {df, name} <- [{first, "1.parquet"}, {second, "2.parquet"}] do
+        assert {:ok, _} = Native.df_write_parquet(df, Path.join(dir, name), "snappy", nil, true)
+      end
+
+      glob = Path.join(dir, "*.parquet")
+      assert {:ok, df} = Native.df_read_dataset([glob], "parquet", nil, nil)
+      assert {:ok, ["i64"]} = Native.df_dtypes(df)
+      assert column(df, "a") == [1, 2]
+    end
+
+    test "returns an error for incompatible schemas", %{dir: dir} do
+      {:ok, a} = Native.s_new_i64("a", [1])
+      {:ok, first} = Native.df_new([a])
+      {:ok, a} = Native.s_new_str("a", ["x"])
+      {:ok, second} = Native.df_new([a])
+
+      for {df, name} <- [{first, "1.parquet"}, {second, "2.parquet"}] do
+        assert {:ok, _} = Native.df_write_parquet(df, Path.join(dir, name), "snappy", nil, true)
+      end
+
+      assert {:error, {:schema_mismatch, _}} =
+               Native.df_read_dataset([Path.join(dir, "*.parquet")], "parquet", nil, nil)
+    end
+
+    test "returns an error for globs without matches", %{dir: dir} do
+      assert {:error, {:other, _}} =
+               Native.df_read_dataset([Path.join(dir, "*.csv")], "csv", nil, nil)
+    end
+  end
+
+  describe "df_write_partitioned" do
+    setup do
+      {:ok, d} = Native.s_new_date32("d", ["2021-01-01", "2021-01-01", "2021-01-02"])
+
+      {:ok, t} =
+        Native.s_new_date64("t", [
+          "2021-01-01T10:00:00",
+          "2021-01-01T11:00:00",
+          "2021-01-01T10:00:00"
+        ])
+
+      {:ok, k} = Native.s_new_f64("k", [1.0, 1.0, 2.5])
+      {:ok, v} = Native.s_new_i64("v", [1, 2, 3])
+      {:ok, df} = Native.df_new([d, t, k, v])
+      {:ok, df: df, root: tmp_path("native_test_partitioned")}
+    end
+
+    test "round trips typed partition keys through df_read_partitioned", %{df: df, root: root} do
+      assert {:ok, _} = Native.df_write_partitioned(df, root, ["d", "t", "k"], "snappy")
+      assert File.dir?(Path.join(root, "d=2021-01-02"))
+
+      assert {:ok, read} = Native.df_read_partitioned(root, [])
+      assert {:ok, read} = Native.df_select(read, ["d", "t", "k", "v"])
+      assert {:ok, true} = Native.df_frame_equal(df, read, true)
+    end
+
+    test "keeps zero-padded keys as strings", %{root: root} do
+      {:ok, z} = Native.s_new_str("z", ["007", "01234", "007"])
+      {:ok, v} = Native.s_new_i64("v", [1, 2, 3])
+      {:ok, df} = Native.df_new([z, v])
+
+      assert {:ok, _} = Native.df_write_partitioned(df, root, ["z"], "snappy")
+      assert {:ok, read} = Native.df_read_partitioned(root, [])
+      assert {:ok, read} = Native.df_select(read, ["z", "v"])
+      assert {:ok, ["str", "i64"]} = Native.df_dtypes(read)
+      assert {:ok, sorted} = Native.df_sort(read, "v", false)
+      assert column(sorted, "z") == ["007", "01234", "007"]
+    end
+
+    test "prunes partitions by key values", %{df: df, root: root} do
+      assert {:ok, _} = Native.df_write_partitioned(df, root, ["d"], "snappy")
+      assert {:ok, read} = Native.df_read_partitioned(root, [{"d", ["2021-01-02"]}])
+      assert column(read, "v") == [3]
+      assert {:error, {:other, _}} = Native.df_read_partitioned(root, [{"v", ["1"]}])
+    end
+  end
+
+  describe "df_csv_schema" do
+    test "infers the dtypes that df_read_csv would read" do
+      path = tmp_path("native_test_csv_schema.csv")
+      File.write!(path, "a,b,c\n1,1.5,x\n2,NA,y\n")
+
+      assert {:ok, [{"a", "i64"}, {"b", "f64"}, {"c", "str"}]} =
+               Native.df_csv_schema(path, nil, true, ",")
+    end
+
+    test "only reads the rows used for inference" do
+      path = tmp_path("native_test_csv_schema_rows.csv")
+      File.write!(path, :zlib.gzip("a;b\n1;x\n2.5;y\n"))
+      assert {:ok, [{"a", "i64"}, {"b", "str"}]} = Native.df_csv_schema(path, 1, true, ";")
+      assert {:ok, [{"a", "f64"}, {"b", "str"}]} = Native.df_csv_schema(path, nil, true, ";")
+    end
+
+    test "returns an error for a missing file" do
+      path = Path.join(System.tmp_dir!(), "native_test_missing.csv")
+      assert {:error, {:io, :enoent}} = Native.df_csv_schema(path, nil, true, ",")
+    end
+  end
+
+  describe "df_parquet_metadata" do
+    test "returns the schema, row counts and statistics" do
+      {:ok, a} = Native.s_new_i64("a", [3, nil, 1, 2])
+      {:ok, b} = Native.s_new_f64("b", [1.5, 2.5, 0.5, nil])
+      {:ok, df} = Native.df_new([a, b])
+      path = tmp_path("native_test_parquet_metadata.parquet")
+      assert {:ok, _} = Native.df_write_parquet(df, path, "snappy", 2, true)
+
+      assert {:ok, metadata} = Native.df_parquet_metadata(path)
+      assert metadata.schema == [{"a", "i64"}, {"b", "f64"}]
+      assert %{num_rows: 4, num_row_groups: 2} = metadata
+
+      assert [
+               %{name: "a", min: 1, max: 3, null_count: 1},
+               %{name: "b", min: 0.5, max: 2.5, null_count: 1}
+             ] = metadata.statistics
+    end
+  end
+
+  test "s_list_sum returns a single chunk" do
+    {:ok, inner} = Native.s_new_i64("", [1, 2])
+    {:ok, s} = Native.s_new_list("s", [inner, nil, inner])
+    assert {:ok, sums} = Native.s_list_sum(s)
+    assert {:ok, [3, nil, 3]} = Native.s_to_list(sums)
+    assert {:ok, 1} = Native.s_n_chunks(sums)
+  end
+
+  test "s_list_contains rejects values of the wrong dtype" do
+    {:ok, inner} = Native.s_new_i64("", [1, 2])
+    {:ok, s} = Native.s_new_list("s", [inner, nil])
+    assert {:error, {:invalid_dtype, _}} = Native.s_list_contains(s, "1")
+  end
+end
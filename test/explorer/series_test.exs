@@ -1,4 +1,153 @@
 defmodule Explorer.SeriesTest do
   use ExUnit.Case, async: true
   doctest Explorer.Series
+
+  alias Explorer.Series
+
+  describe "temporal dtypes" do
+    test "subtracting dates gives a duration that can be added back" do
+      left = Series.from_list([~D[2021-01-03], ~D[2021-01-02], nil])
+      right = Series.from_list([~D[2021-01-01], ~D[2021-01-01], ~D[2021-01-01]])
+
+      duration = Series.subtract(left, right)
+      assert Series.dtype(duration) == :duration
+      assert Series.to_list(duration) == [172_800_000, 86_400_000, nil]
+      assert Series.max(duration) == 172_800_000
+
+      shifted = Series.add(right, duration)
+      assert Series.dtype(shifted) == :datetime
+
+      assert Series.to_list(shifted) == [
+               ~N[2021-01-03 00:00:00.000],
+               ~N[2021-01-02 00:00:00.000],
+               nil
+             ]
+    end
+
+    test "from_list builds a duration series from integer milliseconds" do
+      s = Series.from_list([86_400_000, nil, 3_600_000], dtype: :duration)
+
+      assert Series.dtype(s) == :duration
+      assert Series.to_list(s) == [86_400_000, nil, 3_600_000]
+      assert Series.min(s) == 3_600_000
+
+      shifted = Series.add(Series.from_list([~D[2021-01-01], ~D[2021-01-01], nil]), s)
+      assert Series.to_list(shifted) == [~N[2021-01-02 00:00:00.000], nil, nil]
+    end
+
+    test "from_list rejects a dtype that does not match the values" do
+      assert_raise ArgumentError,
+                   "Cannot make a series of dtype duration from values of dtype float.",
+                   fn -> Series.from_list([1.5], dtype: :duration) end
+    end
+
+    test "time series round trip through the native layer" do
+      s = Series.from_list([~T[12:30:00.000000], nil, ~T[08:15:30.250000]])
+
+      assert Series.dtype(s) == :time
+      assert Series.to_list(s) == [~T[12:30:00.000000], nil, ~T[08:15:30.250000]]
+      assert Series.min(s) == ~T[08:15:30.250000]
+      assert Series.max(s) == ~T[12:30:00.000000]
+      assert s[2] == ~T[08:15:30.250000]
+    end
+  end
+
+  describe "categorical dtype" do
+    test "round trips strings and reports its dtype" do
+      s = Series.from_list(["us", "pt", nil, "us"]) |> Series.cast(:category)
+
+      assert Series.dtype(s) == :category
+      assert Series.to_list(s) == ["us", "pt", nil, "us"]
+      assert s[1] == "pt"
+    end
+
+    test "joins on categoricals from different frames" do
+      alias Explorer.DataFrame, as: DF
+
+      left =
+        DF.from_map(%{country: ["us", "pt"], a: [1, 2]})
+        |> DF.mutate(country: &Series.cast(&1["country"], :category))
+
+      right =
+        DF.from_map(%{country: ["pt", "br", "us"], b: [3, 4, 5]})
+        |> DF.mutate(country: &Series.cast(&1["country"], :category))
+
+      joined = DF.join(left, right) |> DF.arrange(["a"])
+      assert Series.to_list(joined["country"]) == ["us", "pt"]
+      assert Series.to_list(joined["b"]) == [5, 3]
+    end
+  end
+
+  describe "list dtype" do
+    test "round trips nested lists of every primitive dtype" do
+      for list <- [
+            [[1, 2], [], nil, [nil, 3]],
+            [[1.5], nil, [2.5, nil]],
+            [[true, false], nil],
+            [["a"], nil, ["b", nil]],
+            [[~D[2021-01-01]], nil, [~D[2021-01-02], nil]],
+            [[~N[2021-01-01 00:00:00]], nil],
+            [[~T[12:30:00.000000]], nil]
+          ] do
+        s = Series.from_list(list)
+        assert Series.dtype(s) == :list
+        assert Series.to_list(s) == list
+      end
+    end
+
+    test "builds lists whose rows are all empty or nil" do
+      for list <- [[[], []], [[], nil], [[nil], []]] do
+        s = Series.from_list(list)
+        assert Series.dtype(s) == :list
+        assert Series.to_list(s) == list
+      end
+    end
+
+    test "gets rows as lists" do
+      s = Series.from_list([[~D[2021-01-01]], nil])
+      assert s[0] == [~D[2021-01-01]]
+      assert s[1] == nil
+    end
+
+    test "raises on mismatched inner dtypes" do
+      assert_raise ArgumentError, fn -> Series.from_list([[1], [2.5]]) end
+    end
+
+    test "computes per row aggregates" do
+      s = Series.from_list([[3, 1, 2], [4], nil])
+
+      assert Series.to_list(Series.list_sum(s)) == [6, 4, nil]
+      assert Series.to_list(Series.list_min(s)) == [1, 4, nil]
+      assert Series.to_list(Series.list_max(s)) == [3, 4, nil]
+      assert Series.to_list(Series.list_mean(s)) == [2.0, 4.0, nil]
+    end
+
+    test "applies per row functions" do
+      s = Series.from_list([[3, 1, 2], [], nil])
+
+      assert Series.to_list(Series.list_lengths(s)) == [3, 0, nil]
+      assert Series.to_list(Series.list_get(s, 0)) == [3, nil, nil]
+      assert Series.to_list(Series.list_sort(s)) == [[1, 2, 3], [], nil]
+      assert Series.to_list(Series.list_contains(s, 2)) == [true, false, nil]
+      assert Series.to_list(Series.list_join(s, ",")) == ["3,1,2", "", nil]
+    end
+
+    test "raises for non list series" do
+      assert_raise ArgumentError, fn -> Series.list_lengths(Series.from_list([1, 2])) end
+    end
+  end
+
+  describe "struct dtype" do
+    test "raises a clear error for lists of maps" do
+      assert_raise ArgumentError, ~r/struct series are not supported/, fn ->
+        Series.from_list([%{a: 1}, %{a: 2}])
+      end
+    end
+
+    test "does not mistake other structs for struct series" do
+      assert_raise RuntimeError, ~r/Unsupported datatype/, fn ->
+        Series.from_list([~U[2021-01-01 00:00:00Z]])
+      end
+    end
+  end
 end